@@ -0,0 +1,445 @@
+use crate::economy::Economy;
+use crate::item::Rarity;
+use crate::pity::Pity;
+use anyhow::{anyhow, bail, Error as AnyError, Result};
+use item_parser::ItemDb;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Default location of the banner definition file.
+pub const DEFAULT_BANNER_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/res/banners.json");
+
+/// How far the sum of all tier rates may drift away from `1.0`,
+/// rates written in decimals are rarely exact after adding them up.
+const RATE_TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Deserialize)]
+pub struct BannerCfg {
+    pub banners: Vec<Banner>,
+    #[serde(default)]
+    pub economy: Economy,
+    /// Data file of `item_parser` that tiers with a `category` take their items from,
+    /// relative to the banner file.
+    #[serde(default)]
+    pub item_db: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Banner {
+    pub name: String,
+    /// Chance of getting the used ticket back on each pull.
+    #[serde(default)]
+    pub refund_chance: f64,
+    /// Rarity tiers of this banner, rolled in the order they were written.
+    pub tiers: Vec<Tier>,
+    #[serde(default)]
+    pub pity: Option<Pity>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Tier {
+    pub rarity: Rarity,
+    pub rate: f64,
+    #[serde(default)]
+    pub items: Vec<String>,
+    /// Category of the item database to fill the pool with, every entry of the tier's rarity.
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+impl FromStr for BannerCfg {
+    type Err = AnyError;
+    /// The item database is looked for from the current directory.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, Path::new(""))
+    }
+}
+
+impl BannerCfg {
+    /// Read banner definitions from a json file then validate them.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("unable to read banner file '{}': {}", path.display(), e))?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        Self::parse(&raw, dir)
+            .map_err(|e| anyhow!("invalid banner file '{}': {}", path.display(), e))
+    }
+
+    /// Parse banner definitions, fill the pools taken from the item database in `dir`,
+    /// then validate them.
+    fn parse(s: &str, dir: &Path) -> Result<Self> {
+        let mut cfg: Self = serde_json::from_str(s)?;
+        cfg.fill_pools(dir)?;
+        cfg.validate()?;
+        Ok(cfg)
+    }
+
+    /// Put the names of the item database into the pool of every tier with a `category`,
+    /// the database is only loaded if a tier needs it.
+    fn fill_pools(&mut self, dir: &Path) -> Result<()> {
+        let mut db: Option<ItemDb> = None;
+        for banner in &mut self.banners {
+            for tier in &mut banner.tiers {
+                let category = match &tier.category {
+                    Some(category) => category,
+                    None => continue,
+                };
+                let rarity = tier.rarity.stringify();
+                if !tier.items.is_empty() {
+                    bail!(
+                        "banner '{}': tier '{}' has both items and a category",
+                        banner.name,
+                        rarity
+                    );
+                }
+                let db = match &mut db {
+                    Some(db) => db,
+                    None => {
+                        let path = self.item_db.as_ref().ok_or_else(|| {
+                            anyhow!(
+                                "banner '{}': tier '{}' takes its items from category '{}', \
+                                but no item_db was given",
+                                banner.name,
+                                rarity,
+                                category
+                            )
+                        })?;
+                        let path = dir.join(path);
+                        let loaded = ItemDb::load(&path).map_err(|e| {
+                            anyhow!("unable to load item_db '{}': {}", path.display(), e)
+                        })?;
+                        db.insert(loaded)
+                    }
+                };
+                let rarity = rarity.parse().map_err(|e: String| anyhow!(e))?;
+                tier.items = db
+                    .names(rarity, Some(category))
+                    .into_iter()
+                    .map(String::from)
+                    .collect();
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.banners.is_empty() {
+            bail!("no banner was defined");
+        }
+        for (i, banner) in self.banners.iter().enumerate() {
+            if self.banners[..i].iter().any(|b| b.name == banner.name) {
+                bail!("banner '{}' was defined more than once", banner.name);
+            }
+            banner.validate()?;
+        }
+        self.economy.validate()
+    }
+
+    /// Find the index of a banner by its name, case insensitive.
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.banners
+            .iter()
+            .position(|b| b.name.eq_ignore_ascii_case(name))
+    }
+}
+
+impl Banner {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            bail!("banner name cannot be empty");
+        }
+        if !(0.0..=1.0).contains(&self.refund_chance) {
+            bail!(
+                "banner '{}': refund_chance must be between 0 and 1, got {}",
+                self.name,
+                self.refund_chance
+            );
+        }
+        if self.tiers.is_empty() {
+            bail!("banner '{}' has no rarity tier", self.name);
+        }
+
+        for (i, tier) in self.tiers.iter().enumerate() {
+            let rarity = tier.rarity.stringify();
+            if self.tiers[..i].iter().any(|t| t.rarity == tier.rarity) {
+                bail!(
+                    "banner '{}': tier '{}' was defined more than once",
+                    self.name,
+                    rarity
+                );
+            }
+            if !(0.0..=1.0).contains(&tier.rate) {
+                bail!(
+                    "banner '{}': rate of tier '{}' must be between 0 and 1, got {}",
+                    self.name,
+                    rarity,
+                    tier.rate
+                );
+            }
+            if tier.items.is_empty() {
+                bail!(
+                    "banner '{}': item pool of tier '{}' is empty",
+                    self.name,
+                    rarity
+                );
+            }
+        }
+
+        let rate_sum: f64 = self.tiers.iter().map(|t| t.rate).sum();
+        if (rate_sum - 1.0).abs() > RATE_TOLERANCE {
+            bail!(
+                "banner '{}': tier rates must add up to 1, got {}",
+                self.name,
+                rate_sum
+            );
+        }
+
+        if let Some(pity) = &self.pity {
+            self.validate_pity(pity)?;
+        }
+        Ok(())
+    }
+
+    fn validate_pity(&self, pity: &Pity) -> Result<()> {
+        let legendary = self.legendary_tier().ok_or_else(|| {
+            anyhow!(
+                "banner '{}': pity requires a 'Legendary' tier to guarantee",
+                self.name
+            )
+        })?;
+        if pity.hard == 0 || pity.soft >= pity.hard {
+            bail!(
+                "banner '{}': soft pity ({}) must be lower than hard pity ({})",
+                self.name,
+                pity.soft,
+                pity.hard
+            );
+        }
+        if !(0.0..=1.0).contains(&pity.soft_step) {
+            bail!(
+                "banner '{}': soft_step must be between 0 and 1, got {}",
+                self.name,
+                pity.soft_step
+            );
+        }
+        if !(0.0..=1.0).contains(&pity.featured_chance) {
+            bail!(
+                "banner '{}': featured_chance must be between 0 and 1, got {}",
+                self.name,
+                pity.featured_chance
+            );
+        }
+        if let Some(item) = pity.featured.iter().find(|f| !legendary.items.contains(f)) {
+            bail!(
+                "banner '{}': featured item '{}' is not in the 'Legendary' pool",
+                self.name,
+                item
+            );
+        }
+        Ok(())
+    }
+
+    pub fn legendary_tier(&self) -> Option<&Tier> {
+        self.tiers.iter().find(|t| t.rarity == Rarity::Legendary)
+    }
+
+    /// Find the tier that a roll between 0 and 1 falls into.
+    pub fn tier_of_roll(&self, roll: f64) -> &Tier {
+        let mut upper_bound = 0.0;
+        for tier in &self.tiers {
+            upper_bound += tier.rate;
+            if roll < upper_bound {
+                return tier;
+            }
+        }
+        // rates might not add up to exactly 1 due to float precision,
+        // so the last tier takes whatever left
+        self.tiers
+            .last()
+            .expect("banner should have at least one tier")
+    }
+
+    /// Same as [`tier_of_roll`](Banner::tier_of_roll), but the Legendary rate was adjusted
+    /// by the pity model based on how many `pulls` were done since the last Legendary item.
+    ///
+    /// The rest of tiers share whatever chance left while keeping their proportions.
+    pub fn tier_of_roll_with_pity(&self, roll: f64, pulls: u32) -> &Tier {
+        let (pity, legendary) = match (&self.pity, self.legendary_tier()) {
+            (Some(pity), Some(legendary)) => (pity, legendary),
+            _ => return self.tier_of_roll(roll),
+        };
+
+        let rate = pity.legendary_rate(legendary.rate, pulls + 1);
+        if roll < rate {
+            return legendary;
+        }
+
+        // squeeze the remaining roll into the range of non-legendary tiers
+        let scaled_roll = (roll - rate) / (1.0 - rate) * (1.0 - legendary.rate);
+        let mut upper_bound = 0.0;
+        let mut picked = legendary;
+        for tier in self.tiers.iter().filter(|t| t.rarity != Rarity::Legendary) {
+            picked = tier;
+            upper_bound += tier.rate;
+            if scaled_roll < upper_bound {
+                break;
+            }
+        }
+        picked
+    }
+}
+
+#[cfg(test)]
+mod banner_tests {
+    use super::{BannerCfg, DEFAULT_BANNER_FILE};
+    use crate::item::Rarity;
+
+    const SAMPLE: &str = r#"
+    {
+        "banners": [
+            {
+                "name": "Standard",
+                "refund_chance": 0.3,
+                "tiers": [
+                    { "rarity": "Legendary", "rate": 0.1, "items": ["Mona"] },
+                    { "rarity": "Epic", "rate": 0.3, "items": ["Amber", "Razor"] },
+                    { "rarity": "Normal", "rate": 0.6, "items": ["Sword"] }
+                ]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn deserialize_banners() {
+        let cfg = SAMPLE.parse::<BannerCfg>().unwrap();
+        assert_eq!(cfg.banners.len(), 1);
+
+        let banner = &cfg.banners[0];
+        assert_eq!(banner.name, "Standard");
+        assert_eq!(banner.refund_chance, 0.3);
+        assert_eq!(banner.tiers[1].items, ["Amber", "Razor"]);
+        assert_eq!(cfg.position("standard"), Some(0));
+    }
+
+    #[test]
+    fn roll_into_tiers() {
+        let cfg = SAMPLE.parse::<BannerCfg>().unwrap();
+        let banner = &cfg.banners[0];
+        assert_eq!(banner.tier_of_roll(0.0).rarity, Rarity::Legendary);
+        assert_eq!(banner.tier_of_roll(0.1).rarity, Rarity::Epic);
+        assert_eq!(banner.tier_of_roll(0.39).rarity, Rarity::Epic);
+        assert_eq!(banner.tier_of_roll(0.4).rarity, Rarity::Normal);
+        assert_eq!(banner.tier_of_roll(1.0).rarity, Rarity::Normal);
+    }
+
+    #[test]
+    fn reject_bad_rates() {
+        let bad = SAMPLE.replace("\"rate\": 0.6", "\"rate\": 0.5");
+        let err = bad.parse::<BannerCfg>().unwrap_err();
+        assert!(err.to_string().contains("must add up to 1"));
+    }
+
+    #[test]
+    fn reject_empty_pool() {
+        let bad = SAMPLE.replace("[\"Sword\"]", "[]");
+        let err = bad.parse::<BannerCfg>().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("item pool of tier 'Normal' is empty"));
+    }
+
+    #[test]
+    fn roll_with_pity() {
+        let with_pity = SAMPLE.replace(
+            "\"tiers\"",
+            r#""pity": { "soft": 2, "hard": 4, "soft_step": 0.3 }, "tiers""#,
+        );
+        let cfg = with_pity.parse::<BannerCfg>().unwrap();
+        let banner = &cfg.banners[0];
+
+        // no bonus before soft pity
+        assert_eq!(banner.tier_of_roll_with_pity(0.1, 0).rarity, Rarity::Epic);
+        assert_eq!(banner.tier_of_roll_with_pity(0.1, 1).rarity, Rarity::Epic);
+        // the third pull has 0.1 + 0.3 chance
+        assert_eq!(
+            banner.tier_of_roll_with_pity(0.39, 2).rarity,
+            Rarity::Legendary
+        );
+        assert_eq!(banner.tier_of_roll_with_pity(0.4, 2).rarity, Rarity::Epic);
+        // the other tiers keep their proportion: Epic takes 1/3 of what's left
+        assert_eq!(banner.tier_of_roll_with_pity(0.59, 2).rarity, Rarity::Epic);
+        assert_eq!(
+            banner.tier_of_roll_with_pity(0.61, 2).rarity,
+            Rarity::Normal
+        );
+        // the fourth pull is always Legendary
+        assert_eq!(
+            banner.tier_of_roll_with_pity(0.99, 3).rarity,
+            Rarity::Legendary
+        );
+    }
+
+    #[test]
+    fn reject_bad_pity() {
+        let bad = SAMPLE.replace("\"tiers\"", r#""pity": { "soft": 4, "hard": 4 }, "tiers""#);
+        let err = bad.parse::<BannerCfg>().unwrap_err();
+        assert!(err.to_string().contains("must be lower than hard pity"));
+
+        let bad = SAMPLE.replace(
+            "\"tiers\"",
+            r#""pity": { "soft": 2, "hard": 4, "featured": ["Amber"] }, "tiers""#,
+        );
+        let err = bad.parse::<BannerCfg>().unwrap_err();
+        assert!(err.to_string().contains("featured item 'Amber'"));
+    }
+
+    #[test]
+    fn reject_no_banner() {
+        assert!(r#"{ "banners": [] }"#.parse::<BannerCfg>().is_err());
+    }
+
+    #[test]
+    fn pools_from_item_db() {
+        let cfg = BannerCfg::load(DEFAULT_BANNER_FILE).unwrap();
+        let forge = &cfg.banners[cfg.position("Forge").unwrap()];
+        assert_eq!(
+            forge.legendary_tier().unwrap().items,
+            ["Demon Sword", "Stinger of Python"]
+        );
+
+        let with_db = |tiers: &str| {
+            format!(
+                r#"{{ "item_db": "{}/../3_item_parser/res/items.json",
+                    "banners": [{{ "name": "Forge", "tiers": [{}] }}] }}"#,
+                env!("CARGO_MANIFEST_DIR"),
+                tiers
+            )
+        };
+        let cfg = with_db(r#"{ "rarity": "Normal", "rate": 1.0, "category": "weapons" }"#)
+            .parse::<BannerCfg>()
+            .unwrap();
+        assert_eq!(
+            cfg.banners[0].tiers[0].items,
+            ["Iron Sword", "Rusted Sword"]
+        );
+
+        let err = with_db(r#"{ "rarity": "Normal", "rate": 1.0, "category": "characters" }"#)
+            .parse::<BannerCfg>()
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("item pool of tier 'Normal' is empty"));
+        let err = with_db(
+            r#"{ "rarity": "Normal", "rate": 1.0, "items": ["Bow"], "category": "weapons" }"#,
+        )
+        .parse::<BannerCfg>()
+        .unwrap_err();
+        assert!(err.to_string().contains("has both items and a category"));
+
+        let no_db = SAMPLE.replace(r#""items": ["Sword"]"#, r#""category": "weapons""#);
+        let err = no_db.parse::<BannerCfg>().unwrap_err();
+        assert!(err.to_string().contains("but no item_db was given"));
+    }
+}
@@ -0,0 +1,312 @@
+use crate::banner::Banner;
+use crate::item::Rarity;
+use crate::save::SaveData;
+use crate::wallet::{Pricing, Transaction};
+use anyhow::{anyhow, bail, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Rules of converting duplicate items into a secondary currency,
+/// and what that currency could buy from the shop.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Economy {
+    /// Name of the currency earned from duplicates.
+    pub currency: String,
+    /// How much currency one copy of an item of each rarity converts into.
+    pub conversion: HashMap<Rarity, u32>,
+    /// Price of one ticket.
+    pub ticket_price: u32,
+    /// Price of a specific item by its rarity, rarities not listed here cannot be bought.
+    pub item_price: HashMap<Rarity, u32>,
+    /// Ticket cost of pulls.
+    pub pricing: Pricing,
+}
+
+impl Default for Economy {
+    fn default() -> Self {
+        Economy {
+            currency: "Stardust".to_string(),
+            conversion: HashMap::from([
+                (Rarity::Legendary, 200),
+                (Rarity::Epic, 40),
+                (Rarity::Rare, 10),
+                (Rarity::Special, 4),
+                (Rarity::Normal, 1),
+            ]),
+            ticket_price: 20,
+            item_price: HashMap::from([(Rarity::Legendary, 2000), (Rarity::Epic, 400)]),
+            pricing: Pricing::default(),
+        }
+    }
+}
+
+impl Economy {
+    pub fn validate(&self) -> Result<()> {
+        if self.currency.trim().is_empty() {
+            bail!("economy: currency name cannot be empty");
+        }
+        if self.ticket_price == 0 {
+            bail!("economy: ticket_price must be greater than 0");
+        }
+        if let Some((rarity, _)) = self.item_price.iter().find(|(_, price)| **price == 0) {
+            bail!(
+                "economy: price of '{}' items must be greater than 0",
+                rarity.stringify()
+            );
+        }
+        self.pricing.validate()
+    }
+
+    /// Currency earned by converting one copy of an item of given rarity.
+    pub fn conversion_of(&self, rarity: Rarity) -> u32 {
+        self.conversion.get(&rarity).copied().unwrap_or_default()
+    }
+
+    /// Convert `amount` copies of an item into currency, and return how much was earned.
+    ///
+    /// The last copy of an item can never be converted.
+    pub fn convert(
+        &self,
+        save: &mut SaveData,
+        name: &str,
+        rarity: Rarity,
+        amount: u32,
+    ) -> Result<u32> {
+        let owned = save.count_of(name, rarity);
+        if amount == 0 {
+            bail!("nothing to convert");
+        }
+        if amount >= owned {
+            bail!(
+                "you only have {} duplicate(s) of '{}' to convert",
+                owned.saturating_sub(1),
+                name
+            );
+        }
+
+        let earned = self.conversion_of(rarity).saturating_mul(amount);
+        save.remove_item(name, rarity, amount);
+        save.currency = save.currency.saturating_add(earned);
+        Ok(earned)
+    }
+
+    /// Convert every duplicate in the inventory, keeping one copy of each item.
+    ///
+    /// Returns the number of copies converted along with the currency earned.
+    pub fn convert_duplicates(&self, save: &mut SaveData) -> (u32, u32) {
+        let duplicates: Vec<(String, Rarity, u32)> = save
+            .inventory
+            .iter()
+            .filter(|e| e.count > 1)
+            .map(|e| (e.name.clone(), e.rarity, e.count - 1))
+            .collect();
+
+        let (mut converted, mut earned) = (0u32, 0u32);
+        for (name, rarity, amount) in duplicates {
+            if let Ok(e) = self.convert(save, &name, rarity, amount) {
+                converted = converted.saturating_add(amount);
+                earned = earned.saturating_add(e);
+            }
+        }
+        (converted, earned)
+    }
+
+    pub fn buy_tickets(&self, save: &mut SaveData, count: u32) -> Result<u32> {
+        let cost = self
+            .ticket_price
+            .checked_mul(count)
+            .ok_or_else(|| anyhow!("that's way too many tickets"))?;
+        self.check_balance(save, cost)?;
+        save.wallet
+            .apply(Transaction::Purchase { tickets: count })?;
+        save.currency -= cost;
+        Ok(cost)
+    }
+
+    /// Buy a specific item from the pool of a banner, item name is case insensitive.
+    ///
+    /// Returns the name and rarity of the item bought along with its cost.
+    pub fn buy_item(
+        &self,
+        save: &mut SaveData,
+        banner: &Banner,
+        name: &str,
+    ) -> Result<(String, Rarity, u32)> {
+        let (tier, item) = banner
+            .tiers
+            .iter()
+            .find_map(|t| {
+                t.items
+                    .iter()
+                    .find(|i| i.eq_ignore_ascii_case(name))
+                    .map(|i| (t, i))
+            })
+            .ok_or_else(|| anyhow!("banner '{}' has no item named '{}'", banner.name, name))?;
+        let cost = *self.item_price.get(&tier.rarity).ok_or_else(|| {
+            anyhow!(
+                "{} items cannot be bought from the shop",
+                tier.rarity.stringify()
+            )
+        })?;
+
+        self.spend(save, cost)?;
+        save.add_item(item, tier.rarity);
+        Ok((item.clone(), tier.rarity, cost))
+    }
+
+    fn check_balance(&self, save: &SaveData, cost: u32) -> Result<()> {
+        if save.currency < cost {
+            bail!(
+                "not enough {}: {} needed, you have {}",
+                self.currency,
+                cost,
+                save.currency
+            );
+        }
+        Ok(())
+    }
+
+    fn spend(&self, save: &mut SaveData, cost: u32) -> Result<()> {
+        self.check_balance(save, cost)?;
+        save.currency -= cost;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod economy_tests {
+    use super::Economy;
+    use crate::banner::BannerCfg;
+    use crate::item::Rarity;
+    use crate::save::SaveData;
+
+    fn save_with(items: &[(&str, Rarity, u32)]) -> SaveData {
+        let mut save = SaveData::default();
+        for (name, rarity, count) in items {
+            for _ in 0..*count {
+                save.add_item(name, *rarity);
+            }
+        }
+        save
+    }
+
+    #[test]
+    fn convert_duplicates_only() {
+        let economy = Economy::default();
+        let mut save = save_with(&[("Mona", Rarity::Legendary, 3), ("Sword", Rarity::Normal, 1)]);
+
+        assert_eq!(
+            economy
+                .convert(&mut save, "Mona", Rarity::Legendary, 2)
+                .unwrap(),
+            400
+        );
+        assert_eq!(save.count_of("Mona", Rarity::Legendary), 1);
+        assert_eq!(save.currency, 400);
+    }
+
+    #[test]
+    fn never_convert_last_copy() {
+        let economy = Economy::default();
+        let mut save = save_with(&[("Mona", Rarity::Legendary, 2)]);
+
+        assert!(economy
+            .convert(&mut save, "Mona", Rarity::Legendary, 2)
+            .is_err());
+        assert!(economy
+            .convert(&mut save, "Mona", Rarity::Legendary, 0)
+            .is_err());
+        assert!(economy
+            .convert(&mut save, "Klee", Rarity::Legendary, 1)
+            .is_err());
+        assert_eq!(save.count_of("Mona", Rarity::Legendary), 2);
+        assert_eq!(save.currency, 0);
+
+        economy
+            .convert(&mut save, "Mona", Rarity::Legendary, 1)
+            .unwrap();
+        assert!(economy
+            .convert(&mut save, "Mona", Rarity::Legendary, 1)
+            .is_err());
+        assert_eq!(save.count_of("Mona", Rarity::Legendary), 1);
+    }
+
+    #[test]
+    fn convert_all_duplicates() {
+        let economy = Economy::default();
+        let mut save = save_with(&[
+            ("Mona", Rarity::Legendary, 2),
+            ("Amber", Rarity::Epic, 1),
+            ("Sword", Rarity::Normal, 5),
+        ]);
+
+        assert_eq!(economy.convert_duplicates(&mut save), (5, 204));
+        assert!(save.inventory.iter().all(|e| e.count == 1));
+        // nothing left to convert
+        assert_eq!(economy.convert_duplicates(&mut save), (0, 0));
+    }
+
+    #[test]
+    fn convert_huge_duplicates() {
+        let economy = Economy::default();
+        let mut save = save_with(&[("Mona", Rarity::Legendary, 1), ("Amber", Rarity::Epic, 1)]);
+        for entry in &mut save.inventory {
+            entry.count = u32::MAX;
+        }
+        // the totals stop at the maximum instead of overflowing
+        assert_eq!(economy.convert_duplicates(&mut save), (u32::MAX, u32::MAX));
+        assert_eq!(save.currency, u32::MAX);
+    }
+
+    #[test]
+    fn buy_with_exact_balance() {
+        let economy = Economy::default();
+        let mut save = SaveData::default();
+        let tickets = save.wallet.tickets();
+
+        save.currency = 39;
+        assert!(economy.buy_tickets(&mut save, 2).is_err());
+        assert_eq!((save.currency, save.wallet.tickets()), (39, tickets));
+
+        save.currency = 40;
+        assert_eq!(economy.buy_tickets(&mut save, 2).unwrap(), 40);
+        assert_eq!((save.currency, save.wallet.tickets()), (0, tickets + 2));
+        assert!(economy.buy_tickets(&mut save, u32::MAX).is_err());
+    }
+
+    #[test]
+    fn buy_item_from_pool() {
+        let cfg = r#"
+        {
+            "banners": [
+                {
+                    "name": "Test",
+                    "tiers": [
+                        { "rarity": "Legendary", "rate": 0.1, "items": ["Mona"] },
+                        { "rarity": "Normal", "rate": 0.9, "items": ["Sword"] }
+                    ]
+                }
+            ]
+        }"#
+        .parse::<BannerCfg>()
+        .unwrap();
+        let banner = &cfg.banners[0];
+        let economy = Economy::default();
+        let mut save = SaveData {
+            currency: 2000,
+            ..Default::default()
+        };
+
+        assert!(economy.buy_item(&mut save, banner, "Klee").is_err());
+        assert!(economy.buy_item(&mut save, banner, "sword").is_err());
+        let (name, rarity, cost) = economy.buy_item(&mut save, banner, "mona").unwrap();
+        assert_eq!(
+            (name.as_str(), rarity, cost),
+            ("Mona", Rarity::Legendary, 2000)
+        );
+        assert_eq!(save.count_of("Mona", Rarity::Legendary), 1);
+        assert_eq!(save.currency, 0);
+        assert!(economy.buy_item(&mut save, banner, "Mona").is_err());
+    }
+}
@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether [`coloring_str`] adds color codes, see [`set_colored`].
+static COLORED: AtomicBool = AtomicBool::new(true);
+
+#[allow(dead_code)]
+pub enum Color {
+    Red,
+    Yellow,
+    Purple,
+    Blue,
+    Cyan,
+    White,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Rarity {
+    Legendary,
+    Epic,
+    Rare,
+    Special,
+    Normal,
+}
+
+impl Rarity {
+    pub const ALL: [Rarity; 5] = [
+        Rarity::Legendary,
+        Rarity::Epic,
+        Rarity::Rare,
+        Rarity::Special,
+        Rarity::Normal,
+    ];
+
+    /// Find a rarity by its name, case insensitive.
+    pub fn from_name(name: &str) -> Option<Rarity> {
+        Rarity::ALL
+            .into_iter()
+            .find(|r| r.stringify().eq_ignore_ascii_case(name))
+    }
+
+    pub fn stringify(&self) -> &str {
+        match self {
+            Rarity::Legendary => "Legendary",
+            Rarity::Epic => "Epic",
+            Rarity::Rare => "Rare",
+            Rarity::Special => "Special",
+            Rarity::Normal => "Normal",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Rarity::Legendary => Color::Yellow,
+            Rarity::Epic => Color::Purple,
+            Rarity::Rare => Color::Blue,
+            Rarity::Special => Color::Cyan,
+            Rarity::Normal => Color::White,
+        }
+    }
+
+    /// Return the rarity name colored by its rarity.
+    pub fn colored(&self) -> String {
+        coloring_str(self.stringify(), self.color())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Item {
+    pub name: String,
+    pub rarity: Rarity,
+}
+impl Default for Item {
+    fn default() -> Item {
+        Item {
+            name: String::new(),
+            rarity: Rarity::Normal,
+        }
+    }
+}
+impl std::fmt::Display for Item {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            coloring_str(self.name.as_str(), self.rarity.color())
+        )
+    }
+}
+
+/// Turn colors of [`coloring_str`] on or off, they should be off when the output
+/// is not a terminal, e.g. piped into a file or another program.
+pub fn set_colored(enabled: bool) {
+    COLORED.store(enabled, Ordering::Relaxed);
+}
+
+/// Change the color of string literal with specified color in Unix system,
+/// the string is returned as is if colors were turned off by [`set_colored`].
+///
+/// # Example
+/// ```rust
+/// # use gotcha_game::item::{coloring_str, Color};
+/// let red_bold_hello = coloring_str("Hello!", Color::Red);
+/// assert_eq!(red_bold_hello, "\x1b[31;1mHello!\x1b[0m");
+/// println!("{}", red_bold_hello); // prints a "Hello!" message in red
+/// ```
+pub fn coloring_str(word: &str, color: Color) -> String {
+    if !COLORED.load(Ordering::Relaxed) {
+        return word.to_string();
+    }
+    match color {
+        Color::Red => format!("\x1b[31;1m{}\x1b[0m", word),
+        Color::Yellow => format!("\x1b[33;1m{}\x1b[0m", word),
+        Color::Purple => format!("\x1b[35;1m{}\x1b[0m", word),
+        Color::Blue => format!("\x1b[34;1m{}\x1b[0m", word),
+        Color::Cyan => format!("\x1b[36;1m{}\x1b[0m", word),
+        Color::White => format!("\x1b[37;1m{}\x1b[0m", word),
+    }
+}
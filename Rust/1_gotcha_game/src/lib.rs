@@ -0,0 +1,211 @@
+pub mod banner;
+pub mod economy;
+pub mod item;
+pub mod pity;
+pub mod rng_helper;
+pub mod save;
+pub mod session;
+pub mod simulate;
+pub mod wallet;
+
+use anyhow::Result;
+use banner::Banner;
+use item::{Item, Rarity};
+use pity::PlayerState;
+use rng_helper::Random;
+use serde::Serialize;
+use wallet::{Pricing, Wallet};
+
+/// Result of a single pull.
+#[derive(Debug, Serialize)]
+pub struct Pull {
+    #[serde(flatten)]
+    pub item: Item,
+    /// Whether the ticket used on this pull was refunded.
+    pub refunded: bool,
+}
+
+/// Pull `count` times from a banner and return the items being pulled along
+/// with whether their tickets were refunded.
+///
+/// Pity progress of the banner is read from and saved into the player's `state`,
+/// every random roll comes from `rng`, so the same seed gives the same pulls.
+pub fn pull(banner: &Banner, state: &mut PlayerState, rng: &mut Random, count: u8) -> Vec<Pull> {
+    let mut pulls: Vec<Pull> = vec![];
+    let counter = state.pity_mut(&banner.name);
+
+    for _ in 0..count {
+        // generate a float between 0-1
+        let item_roll_num: f64 = rng.float();
+        let tier = banner.tier_of_roll_with_pity(item_roll_num, counter.pulls);
+
+        let mut pool: Vec<&String> = tier.items.iter().collect();
+        if tier.rarity == Rarity::Legendary {
+            counter.pulls = 0;
+            if let Some(pity) = &banner.pity {
+                let featured = pity.roll_featured(counter, rng.float());
+                let sub_pool: Vec<&String> = pool
+                    .iter()
+                    .copied()
+                    .filter(|name| pity.featured.contains(name) == featured)
+                    .collect();
+                // every legendary item might be featured, nothing to choose from then
+                if !sub_pool.is_empty() {
+                    pool = sub_pool;
+                }
+            }
+        } else {
+            counter.pulls += 1;
+        }
+
+        let item = Item {
+            name: rng
+                .element_in_slice(&pool)
+                .expect("item pool should not be empty")
+                .to_string(),
+            rarity: tier.rarity,
+        };
+
+        // generate a float between 0 - 1 for wether refund the material used or not
+        let refunded = rng.float() < banner.refund_chance;
+
+        pulls.push(Pull { item, refunded });
+    }
+
+    pulls
+}
+
+/// Pay for `count` pulls from the `wallet`, pull, then give back the tickets of refunded pulls.
+///
+/// Returns the pulls along with how many tickets were refunded. Nothing is pulled
+/// if there isn't enough tickets.
+pub fn paid_pull(
+    banner: &Banner,
+    state: &mut PlayerState,
+    rng: &mut Random,
+    wallet: &mut Wallet,
+    pricing: &Pricing,
+    count: u8,
+) -> Result<(Vec<Pull>, u32)> {
+    let paid = wallet.pay_for_pulls(pricing, u32::from(count))?;
+    let pulls = pull(banner, state, rng, count);
+    let refunded = pulls.iter().filter(|p| p.refunded).count() as u32;
+    let refund = wallet.refund(pricing, paid, refunded)?;
+    Ok((pulls, refund.tickets()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{paid_pull, pull, PlayerState, Random, Rarity};
+    use crate::banner::BannerCfg;
+    use crate::wallet::{Pricing, Wallet};
+
+    const SAMPLE: &str = r#"
+    {
+        "banners": [
+            {
+                "name": "Test",
+                "tiers": [
+                    { "rarity": "Legendary", "rate": 0.0, "items": ["Mona", "Klee"] },
+                    { "rarity": "Normal", "rate": 1.0, "items": ["Sword"] }
+                ],
+                "pity": { "soft": 5, "hard": 10, "featured": ["Mona"], "featured_chance": 0.0 }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn hard_pity_boundary() {
+        let cfg = SAMPLE.parse::<BannerCfg>().unwrap();
+        let banner = &cfg.banners[0];
+        let mut state = PlayerState::default();
+        let mut rng = Random::with_seed(0);
+
+        // legendary rate was set to 0, so only pity could give one
+        let items = pull(banner, &mut state, &mut rng, 9);
+        assert!(items.iter().all(|p| p.item.rarity == Rarity::Normal));
+        assert_eq!(state.pity("Test").pulls, 9);
+
+        let items = pull(banner, &mut state, &mut rng, 1);
+        assert_eq!(items[0].item.rarity, Rarity::Legendary);
+        assert_eq!(state.pity("Test").pulls, 0);
+
+        // counter starts over after the guarantee
+        let items = pull(banner, &mut state, &mut rng, 9);
+        assert!(items.iter().all(|p| p.item.rarity == Rarity::Normal));
+    }
+
+    #[test]
+    fn featured_guarantee_carries_over() {
+        let cfg = SAMPLE.parse::<BannerCfg>().unwrap();
+        let banner = &cfg.banners[0];
+        let mut state = PlayerState::default();
+        let mut rng = Random::with_seed(0);
+
+        // featured chance is 0, so the first legendary always loses the 50/50
+        let items = pull(banner, &mut state, &mut rng, 10);
+        assert_eq!(items[9].item.name, "Klee");
+        assert!(state.pity("Test").guaranteed_featured);
+
+        let items = pull(banner, &mut state, &mut rng, 10);
+        assert_eq!(items[9].item.name, "Mona");
+        assert!(!state.pity("Test").guaranteed_featured);
+    }
+
+    #[test]
+    fn seeded_pull_history() {
+        let cfg = r#"
+        {
+            "banners": [
+                {
+                    "name": "Snapshot",
+                    "refund_chance": 0.3,
+                    "tiers": [
+                        { "rarity": "Legendary", "rate": 0.2, "items": ["Mona", "Klee"] },
+                        { "rarity": "Epic", "rate": 0.3, "items": ["Amber", "Razor"] },
+                        { "rarity": "Normal", "rate": 0.5, "items": ["Sword", "Bow"] }
+                    ]
+                }
+            ]
+        }"#
+        .parse::<BannerCfg>()
+        .unwrap();
+        let banner = &cfg.banners[0];
+        let history = |seed: u64| {
+            let mut state = PlayerState::default();
+            let mut rng = Random::with_seed(seed);
+            let pulls = pull(banner, &mut state, &mut rng, 10);
+            let refund = pulls.iter().filter(|p| p.refunded).count();
+            let names: Vec<String> = pulls.into_iter().map(|p| p.item.name).collect();
+            (names, refund)
+        };
+
+        assert_eq!(history(42), history(42));
+        assert_ne!(history(42), history(43));
+        let names = [
+            "Sword", "Razor", "Mona", "Sword", "Bow", "Bow", "Sword", "Amber", "Razor", "Mona",
+        ];
+        assert_eq!(history(42), (names.map(String::from).to_vec(), 5));
+    }
+
+    #[test]
+    fn paid_pull_balance() {
+        let cfg = SAMPLE.parse::<BannerCfg>().unwrap();
+        let banner = &cfg.banners[0];
+        let pricing = Pricing::default();
+        let mut state = PlayerState::default();
+        let mut rng = Random::with_seed(0);
+
+        let mut wallet = Wallet::new(8);
+        assert!(paid_pull(banner, &mut state, &mut rng, &mut wallet, &pricing, 10).is_err());
+        assert_eq!(wallet.tickets(), 8);
+        assert_eq!(state.pity("Test").pulls, 0);
+
+        // the sample banner never refunds
+        let mut wallet = Wallet::new(10);
+        let (pulls, refund) =
+            paid_pull(banner, &mut state, &mut rng, &mut wallet, &pricing, 10).unwrap();
+        assert_eq!((pulls.len(), refund), (10, 0));
+        assert_eq!(wallet.tickets(), 1);
+    }
+}
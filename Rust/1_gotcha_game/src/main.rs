@@ -1,332 +1,543 @@
-mod rng_helper;
+use anyhow::{anyhow, Result};
+use clap::{value_parser, Arg, ArgAction, ArgGroup, ArgMatches, Command};
+use gotcha_game::banner::{Banner, BannerCfg, DEFAULT_BANNER_FILE};
+use gotcha_game::item::{coloring_str, set_colored, Color, Item, Rarity};
+use gotcha_game::pity::PlayerState;
+use gotcha_game::rng_helper::Random;
+use gotcha_game::save::{format_timestamp, SaveData, DEFAULT_SAVE_FILE};
+use gotcha_game::session::{Action, Event, Session};
+use gotcha_game::simulate;
+use gotcha_game::wallet::{NotEnoughTickets, Pricing, Wallet};
+use serde_json::json;
+use std::io::{self, IsTerminal, Write};
 
-use rng_helper::random;
-use std::io::{self, Write};
-
-// example database for items with different rarity
-static LEGENDARY_ITEMS: [&str; 15] = [
-    "Mona",
-    "Diluc",
-    "Klee",
-    "Jean",
-    "Venti",
-    "Albedo",
-    "Keqing",
-    "Xiao",
-    "Qiqi",
-    "Zhongli",
-    "Ganyu",
-    "Raiden Shogun",
-    "Yomiya",
-    "Itto",
-    "Kamisato Ayaka",
-];
-static EPIC_ITEMS: [&str; 20] = [
-    "Barbara",
-    "Amber",
-    "Bennet",
-    "Sucross",
-    "Razor",
-    "Xiangling",
-    "Xingqiu",
-    "Chongyun",
-    "Fischle",
-    "Xinyan",
-    "Yanfei",
-    "Ningguang",
-    "Black Sword",
-    "The Flute",
-    "Stringless",
-    "Rust",
-    "Prototype Sword",
-    "Prototype Great Sword",
-    "Prototype Bow",
-    "Prototype Spear",
-];
-static RARE_ITEMS: [&str; 15] = [
-    "Black Steel Set - Head",
-    "Black Steel Set - Body",
-    "Black Steel Set - Torso",
-    "Black Steel Set - Foot",
-    "Black Steel Set - Cape",
-    "Black Steel Sword",
-    "Black Steel Shield",
-    "Black Steel Sword",
-    "Ruby Necklace - ATK%",
-    "Sapphire Ring - MAGIC%",
-    "Moonlight Wand",
-    "The Invader",
-    "Debate Club",
-    "Alsa's Cape",
-    "Staff of Walnut",
-];
-static SPECIAL_ITEMS: [&str; 15] = [
-    "Silver Sword",
-    "Silver Helmet",
-    "Silver Armor",
-    "Silver Shield",
-    "Silver Boots",
-    "Steel Sword",
-    "Steel Shield",
-    "Silver Helmet",
-    "Silver Armor",
-    "Silver Boots",
-    "Wand of Gandoff",
-    "Hat of Agatha",
-    "Cape of Agatha",
-    "Boots of Agatha",
-    "The Charger",
-];
-static NORMAL_ITEMS: [&str; 10] = [
-    "Sword",
-    "Wooden Staff",
-    "Magic Wand",
-    "Bow",
-    "Free Lance",
-    "Leather Coat",
-    "Leather Pants",
-    "Leather Shoes",
-    "Leather Hat",
-    "Wedding Ring",
-];
-
-#[allow(dead_code)]
-enum Color {
-    Red,
-    Yellow,
-    Purple,
-    Blue,
-    Cyan,
-    White,
+fn rule_msg(banner: &Banner, pricing: &Pricing, ticket_count: u32, ticket_name: &str) -> String {
+    let rarities = banner
+        .tiers
+        .iter()
+        .map(|t| t.rarity.colored())
+        .collect::<Vec<_>>()
+        .join("/");
+    format!(
+        "
+-----------------------------------------------------------------------------
+            Hello! Welcome to my very boring gotcha game.
+    You have {0} {1}s left to play, each pull uses one {1} (or {4} for ten pulls)
+    for a chance to get one {2} item from the \"{3}\" banner.
+    Additionaly, you'll have a slight changce getting your {1} back~
+    Enjoy!
+-----------------------------------------------------------------------------",
+        ticket_count,
+        ticket_name,
+        rarities,
+        banner.name,
+        pricing.cost_of(10)
+    )
 }
-enum Rarity {
-    Legendary,
-    Epic,
-    Rare,
-    Special,
-    Normal,
+
+fn banner_list_msg(cfg: &BannerCfg, current: usize) -> String {
+    let mut msg = String::from("Available banners:");
+    for (i, banner) in cfg.banners.iter().enumerate() {
+        let marker = if i == current { "*" } else { " " };
+        msg.push_str(&format!("\n  {} [{}] {}", marker, i + 1, banner.name));
+    }
+    msg
 }
 
-impl Rarity {
-    fn stringify(&self) -> &str {
-        match self {
-            Rarity::Legendary => "Legendary",
-            Rarity::Epic => "Epic",
-            Rarity::Rare => "Rare",
-            Rarity::Special => "Special",
-            Rarity::Normal => "Normal",
-        }
+fn pity_msg(banner: &Banner, state: &PlayerState) -> String {
+    let pity = match &banner.pity {
+        Some(pity) => pity,
+        None => return format!("Banner \"{}\" has no pity.", banner.name),
+    };
+    let counter = state.pity(&banner.name);
+    let mut msg = format!(
+        "{0} pull(s) since your last {1} item, the odds goes up after {2} pulls and {1} is guaranteed at pull {3}.",
+        counter.pulls,
+        Rarity::Legendary.colored(),
+        pity.soft,
+        pity.hard
+    );
+    if !pity.featured.is_empty() {
+        msg.push_str(&format!(
+            "\nFeatured: {}{}",
+            pity.featured.join(", "),
+            if counter.guaranteed_featured {
+                " (your next one is guaranteed to be featured!)"
+            } else {
+                ""
+            }
+        ));
     }
+    msg
 }
 
-struct Item {
-    name: String,
-    rarity: Rarity,
+fn app() -> Command {
+    Command::new(env!("CARGO_PKG_NAME"))
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("A very basic gotcha game")
+        .arg(
+            Arg::new("banners")
+                .long("banners")
+                .value_name("PATH")
+                .help("Path to the banner definition file")
+                .global(true)
+                .default_value(DEFAULT_BANNER_FILE),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .value_name("SEED")
+                .help("Seed of the random generator, the same seed always gives the same pulls")
+                .global(true)
+                .value_parser(value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("save")
+                .long("save")
+                .value_name("PATH")
+                .help("Path to the save file, a new game starts if it doesn't exist")
+                .default_value(DEFAULT_SAVE_FILE),
+        )
+        .arg(
+            Arg::new("no-save")
+                .long("no-save")
+                .help("Start a new game and never write the save file")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("tickets")
+                .long("tickets")
+                .value_name("COUNT")
+                .help("Number of tickets the new game starts with")
+                .value_parser(value_parser!(u32))
+                .requires("no-save"),
+        )
+        .arg(
+            Arg::new("banner")
+                .long("banner")
+                .value_name("NUMBER|NAME")
+                .help("Banner to start on, defaults to the first one"),
+        )
+        .arg(
+            Arg::new("pulls")
+                .long("pulls")
+                .value_name("COUNT")
+                .help("Pull a number of times without prompting, then exit")
+                .value_parser(value_parser!(u32)),
+        )
+        .arg(Arg::new("script").long("script").value_name("PATH").help(
+            "Run in-game commands from a file (or '-' for stdin) without prompting, then exit",
+        ))
+        .group(ArgGroup::new("batch").args(["pulls", "script"]))
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .help("Output format of --pulls and --script")
+                .value_parser(["text", "json"])
+                .default_value("text")
+                .requires("batch"),
+        )
+        .subcommand(
+            Command::new("simulate")
+                .visible_alias("sim")
+                .about("Simulate a large number of pulls and verify the rates of a banner")
+                .arg(
+                    Arg::new("banner")
+                        .long("banner")
+                        .value_name("NAME")
+                        .help("Name of the banner to simulate, defaults to the first one"),
+                )
+                .arg(
+                    Arg::new("pulls")
+                        .long("pulls")
+                        .value_name("COUNT")
+                        .help("Number of pulls to simulate")
+                        .value_parser(value_parser!(u64))
+                        .default_value("1000000"),
+                )
+                .arg(
+                    Arg::new("threads")
+                        .long("threads")
+                        .value_name("COUNT")
+                        .help("Number of threads to run on, defaults to the number of CPUs")
+                        .value_parser(value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("tolerance")
+                        .long("tolerance")
+                        .value_name("RATE")
+                        .help("Maximum difference allowed between the observed and expected rates")
+                        .value_parser(value_parser!(f64))
+                        .default_value("0.005"),
+                ),
+        )
 }
-impl Default for Item {
-    fn default() -> Item {
-        Item {
-            name: String::new(),
-            rarity: Rarity::Normal,
-        }
+
+fn inventory_msg(save: &SaveData) -> String {
+    let inventory = save.sorted_inventory();
+    if inventory.is_empty() {
+        return "Your inventory is empty.".to_string();
     }
-}
-impl std::fmt::Display for Item {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let res_str = match self.rarity {
-            Rarity::Legendary => coloring_str(self.name.as_str(), Color::Yellow),
-            Rarity::Epic => coloring_str(self.name.as_str(), Color::Purple),
-            Rarity::Rare => coloring_str(self.name.as_str(), Color::Blue),
-            Rarity::Special => coloring_str(self.name.as_str(), Color::Cyan),
-            Rarity::Normal => coloring_str(self.name.as_str(), Color::White),
+    let mut msg = String::from("Inventory:");
+    for entry in inventory {
+        let item = Item {
+            name: entry.name.clone(),
+            rarity: entry.rarity,
         };
-
-        write!(f, "{}", res_str)
+        msg.push_str(&format!(
+            "\n  [{}] {} x{}",
+            entry.rarity.colored(),
+            item,
+            entry.count
+        ));
     }
+    msg
 }
 
-/// Change the color of string literal with specified color in Unix system
-///
-/// # Example
-/// ```rust
-/// let red_bold_hello = coloring_str("Hello!", Color::Red);
-/// assert_eq!(red_bold_hello, "\x1b[31;1mHello!\x1b[0m");
-/// println!("{}", red_bold_hello); // prints a "Hello!" message in red
-/// ```
-fn coloring_str(word: &str, color: Color) -> String {
-    match color {
-        Color::Red => format!("\x1b[31;1m{}\x1b[0m", word),
-        Color::Yellow => format!("\x1b[33;1m{}\x1b[0m", word),
-        Color::Purple => format!("\x1b[35;1m{}\x1b[0m", word),
-        Color::Blue => format!("\x1b[34;1m{}\x1b[0m", word),
-        Color::Cyan => format!("\x1b[36;1m{}\x1b[0m", word),
-        Color::White => format!("\x1b[37;1m{}\x1b[0m", word),
+fn history_msg(save: &SaveData, rarity: Option<Rarity>) -> String {
+    let mut msg = String::from("History:");
+    for record in save.history_of(rarity) {
+        let item = Item {
+            name: record.item.clone(),
+            rarity: record.rarity,
+        };
+        msg.push_str(&format!(
+            "\n  {} ({}) {}{}",
+            format_timestamp(record.timestamp),
+            record.banner,
+            item,
+            if record.refunded { " (refunded)" } else { "" }
+        ));
     }
+    msg
 }
 
-/// Pull `count` times and return the item being pulled along
-/// with how many resource refunded.
-fn pull(count: u8) -> (Vec<Item>, u8) {
-    const MAX_OF_LEGENDARY: f64 = 0.05;
-    const MAX_OF_EPIC: f64 = 0.08 + MAX_OF_LEGENDARY;
-    const MAX_OF_RARE: f64 = 0.12 + MAX_OF_EPIC;
-    const MAX_OF_SPECIAL: f64 = 0.15 + MAX_OF_RARE;
-
-    const CHANCE_OF_REFUND: f64 = 0.3;
+fn shop_msg(banners: &BannerCfg, banner: &Banner, save: &SaveData, ticket_name: &str) -> String {
+    let economy = &banners.economy;
+    let mut msg = format!(
+        "You have {0} {1}.\n  {2} {1} for a {3}",
+        save.currency, economy.currency, economy.ticket_price, ticket_name
+    );
+    for tier in &banner.tiers {
+        if let Some(price) = economy.item_price.get(&tier.rarity) {
+            msg.push_str(&format!(
+                "\n  {} {} for any {} item of banner \"{}\"",
+                price,
+                economy.currency,
+                tier.rarity.colored(),
+                banner.name
+            ));
+        }
+    }
+    msg
+}
 
-    let mut refund_count: u8 = 0;
-    let mut items_rolled: Vec<Item> = vec![];
+fn export_history(save: &SaveData, path: &str) -> Result<()> {
+    let file = std::fs::File::create(path)?;
+    save.export_csv(io::BufWriter::new(file))
+}
 
-    for _ in 0..count {
-        let mut item = Item::default();
+/// Describe what happened after an action.
+fn event_msg(event: &Event, currency: &str, ticket_name: &str) -> String {
+    match event {
+        Event::Pulled { items, refund, .. } => {
+            let res_str = items
+                .iter()
+                .map(|p| p.item.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            match refund {
+                0 => format!("Pulled: [{}]", res_str),
+                1 if items.len() == 1 => {
+                    format!("Pulled: [{0}] (You got a {1} back!)", res_str, ticket_name)
+                }
+                _ => format!(
+                    "Pulled: [{0}] (You got {2} {1}s back!)",
+                    res_str, ticket_name, refund
+                ),
+            }
+        }
+        Event::Switched { banner } => format!("Switched to banner \"{}\".", banner),
+        Event::Converted { copies, earned } => format!(
+            "Converted {} duplicate(s) into {} {}.",
+            copies, earned, currency
+        ),
+        Event::BoughtTickets { count, cost } => format!(
+            "Bought {} {}(s) with {} {}.",
+            count, ticket_name, cost, currency
+        ),
+        Event::BoughtItem { item, cost } => {
+            format!("Bought [{}] with {} {}.", item, cost, currency)
+        }
+    }
+}
 
-        // generate a float between 0-1
-        let item_roll_num: f64 = random::float();
+/// Commands to run without prompting, along with how they were written.
+type Script = Vec<(String, Result<Action>)>;
 
-        if item_roll_num < MAX_OF_LEGENDARY {
-            item.rarity = Rarity::Legendary;
-            item.name = random::element_in_slice(&LEGENDARY_ITEMS)
-                .unwrap()
-                .to_string();
-        } else if item_roll_num < MAX_OF_EPIC {
-            item.rarity = Rarity::Epic;
-            item.name = random::element_in_slice(&EPIC_ITEMS).unwrap().to_string();
-        } else if item_roll_num < MAX_OF_RARE {
-            item.rarity = Rarity::Rare;
-            item.name = random::element_in_slice(&RARE_ITEMS).unwrap().to_string();
-        } else if item_roll_num < MAX_OF_SPECIAL {
-            item.rarity = Rarity::Special;
-            item.name = random::element_in_slice(&SPECIAL_ITEMS)
-                .unwrap()
-                .to_string();
-        } else {
-            item.rarity = Rarity::Normal;
-            item.name = random::element_in_slice(&NORMAL_ITEMS).unwrap().to_string();
-        }
+/// Read commands from either `--pulls` or `--script`.
+fn batch_actions(m: &ArgMatches, pricing: &Pricing) -> Result<Option<Script>> {
+    if let Some(pulls) = m.get_one::<u32>("pulls") {
+        let actions = Action::pulls(*pulls, pricing)
+            .into_iter()
+            .map(|action| (action.to_string(), Ok(action)))
+            .collect();
+        return Ok(Some(actions));
+    }
+    let path = match m.get_one::<String>("script") {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+    let script = if path == "-" {
+        io::read_to_string(io::stdin())?
+    } else {
+        std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("unable to read script '{}': {}", path, e))?
+    };
+    // blank lines and comments are skipped, so an empty line doesn't pull like it does in game
+    let actions = script
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let action = Action::parse(line).and_then(|action| {
+                action.ok_or_else(|| anyhow!("'{}' is not supported in batch mode", line))
+            });
+            (line.to_string(), action)
+        })
+        .collect();
+    Ok(Some(actions))
+}
 
-        // generate a float between 0 - 1 for wether refund the material used or not
-        if random::float() < CHANCE_OF_REFUND {
-            refund_count += 1;
+/// Run commands one by one and print what happened, stops at the first failing command.
+///
+/// In JSON format, a single object with every event and the final state of the game is printed.
+fn run_batch(session: &mut Session, actions: Script, json: bool, ticket_name: &str) -> Result<()> {
+    let economy = &session.cfg.economy;
+    let mut events = vec![];
+    let mut failure = None;
+    for (cmd, action) in actions {
+        match action.and_then(|action| session.execute(action)) {
+            Ok(event) if json => {
+                let mut value = serde_json::to_value(&event)?;
+                value["command"] = cmd.into();
+                events.push(value);
+            }
+            Ok(event) => println!("{}", event_msg(&event, &economy.currency, ticket_name)),
+            Err(e) => {
+                failure = Some(anyhow!("command '{}' failed: {}", cmd, e));
+                break;
+            }
         }
-
-        items_rolled.push(item);
     }
 
-    (items_rolled, refund_count)
+    let save = &session.save;
+    if json {
+        let report = json!({
+            "events": events,
+            "error": failure.as_ref().map(|e| e.to_string()),
+            "banner": session.banner().name,
+            "tickets": save.wallet.tickets(),
+            "currency": save.currency,
+            "inventory": save.sorted_inventory(),
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!(
+            "{} {}(s) and {} {} left.",
+            save.wallet.tickets(),
+            ticket_name,
+            save.currency,
+            economy.currency
+        );
+    }
+    failure.map_or(Ok(()), Err)
 }
 
-fn main() {
+fn main() -> Result<()> {
     const NAME_OF_TICKET: &str = "Ticket";
 
-    let mut ticket_count: u32 = 100;
+    let m = app().get_matches();
+    set_colored(io::stdout().is_terminal());
+    let banner_file = m
+        .get_one::<String>("banners")
+        .expect("banner file has a default value");
+    let banners = BannerCfg::load(banner_file)?;
+    let seed = m.get_one::<u64>("seed").copied();
+    if let Some(("simulate", am)) = m.subcommand() {
+        return simulate::simulate(am, &banners, seed);
+    }
+
+    let rng = match seed {
+        Some(seed) => Random::with_seed(seed),
+        None => Random::new(),
+    };
+    let save_file = if m.get_flag("no-save") {
+        None
+    } else {
+        m.get_one::<String>("save")
+    };
+    let mut save = match save_file {
+        Some(path) => SaveData::load_or_new(path)?,
+        None => SaveData::default(),
+    };
+    if let Some(tickets) = m.get_one::<u32>("tickets") {
+        save.wallet = Wallet::new(*tickets);
+    }
+    let store = |save: &SaveData| save_file.map_or(Ok(()), |path| save.store(path));
 
-    let rule_msg: String = format!(
-        "
------------------------------------------------------------------------------
-            Hello! Welcome to my very boring gotcha game.
-    You have {0} {1}s left to play, each pull uses one {1}
-    for a chance to get one {2}/{3}/{4}/{5}/{6} item.
-    Additionaly, you'll have a slight changce getting your {1} back~
-    Enjoy!
------------------------------------------------------------------------------",
-        ticket_count,
-        NAME_OF_TICKET,
-        coloring_str(Rarity::Legendary.stringify(), Color::Yellow),
-        coloring_str(Rarity::Epic.stringify(), Color::Purple),
-        coloring_str(Rarity::Rare.stringify(), Color::Blue),
-        coloring_str(Rarity::Special.stringify(), Color::Cyan),
-        coloring_str(Rarity::Normal.stringify(), Color::White)
-    );
+    let mut session = Session::new(&banners, save, rng);
+    if let Some(target) = m.get_one::<String>("banner") {
+        session.execute(Action::Switch(target.clone()))?;
+    }
+
+    if let Some(actions) = batch_actions(&m, &banners.economy.pricing)? {
+        let json = m.get_one::<String>("format").is_some_and(|f| f == "json");
+        let res = run_batch(&mut session, actions, json, NAME_OF_TICKET);
+        store(&session.save)?;
+        return res;
+    }
 
     let help_msg: String = format!(
         "
     Enter 
         \"{0}\": to show game rules.
         \"{1}\": to show this help message.
-        \"{2}\": to do ten pulls at once.
-        \"{3}\": to exit the game.
+        \"{2}\": to do ten pulls at once for {12} {13}s.
+        \"{3}\": to list banners, or \"{3} <NUMBER|NAME>\" to switch banner.
+        \"{4}\": to show pity progress of current banner.
+        \"{5}\": to show your inventory.
+        \"{6}\": to show pull history, or \"{6} <RARITY>\" to only show items of a rarity.
+        \"{7}\": to export pull history as CSV to a file.
+        \"{8}\": to convert all duplicates into {11}, or \"{8} <NAME>\" to convert one copy of an item.
+        \"{9}\": to visit the shop, \"{9} ticket <COUNT>\" or \"{9} item <NAME>\" to buy.
+        \"{10}\": to exit the game.
     Or enter nothing for a single pull.
         ",
-        "r", "h", "10", "e"
+        "r", "h", "10", "b", "p", "i", "l", "x <PATH>", "c", "s", "e", banners.economy.currency, banners.economy.pricing.cost_of(10), NAME_OF_TICKET
     );
 
-    println!("{}", rule_msg);
+    println!(
+        "{}",
+        rule_msg(
+            session.banner(),
+            &banners.economy.pricing,
+            session.save.wallet.tickets(),
+            NAME_OF_TICKET
+        )
+    );
     println!("{}", help_msg);
 
     // main game loop
-    'game: loop {
+    loop {
+        let banner = session.banner();
         let mut input = String::new();
 
-        print!("{0} {1}(s) left: ", ticket_count, NAME_OF_TICKET);
+        print!(
+            "{0} {1}(s) left: ",
+            session.save.wallet.tickets(),
+            NAME_OF_TICKET
+        );
         let _ = io::stdout().flush();
-        io::stdin()
-            .read_line(&mut input)
-            .expect("Fail to read input, try again.");
+        // nothing left to read, e.g. the input was piped in
+        if io::stdin().read_line(&mut input)? == 0 {
+            println!();
+            break;
+        }
 
         match input.trim() {
-            "" => {
-                if ticket_count > 0 {
-                    let (res, refund) = pull(1);
-                    if refund > 0 {
-                        println!(
-                            "Pulled: [{0}] (You got a {1} back!)",
-                            res[0], NAME_OF_TICKET
-                        );
-                    } else {
-                        ticket_count -= 1;
-                        println!("Pulled: [{}]", res[0]);
-                    }
-                } else {
-                    println!(
-                        "{}",
-                        coloring_str(
-                            format!("Sorry, you ran out of {}s", NAME_OF_TICKET).as_str(),
-                            Color::Red
-                        )
-                    );
-                    break 'game;
-                }
-            }
             "r" => {
-                println!("{}", rule_msg);
+                println!(
+                    "{}",
+                    rule_msg(
+                        banner,
+                        &banners.economy.pricing,
+                        session.save.wallet.tickets(),
+                        NAME_OF_TICKET
+                    )
+                );
             }
             "h" => {
                 println!("{}", help_msg);
             }
-            "10" => {
-                if ticket_count >= 10 {
-                    let (res, refund) = pull(10);
-                    let mut res_str = String::new();
-                    for r in &res {
-                        res_str = format!("{}, {}", res_str, &r);
-                    }
-
-                    if refund > 0 {
-                        println!(
-                            "Pulled: [{0}] (You got {2} {1}s back!)",
-                            res_str, NAME_OF_TICKET, refund
-                        );
-                        ticket_count = ticket_count - 10 + refund as u32;
-                    } else {
-                        println!("Pulled: [{}]", res_str);
-                        ticket_count -= 10;
-                    }
-                } else {
-                    println!(
+            "b" => {
+                println!("{}", banner_list_msg(&banners, session.banner_idx()));
+            }
+            "p" => {
+                println!("{}", pity_msg(banner, &session.save.state));
+            }
+            "i" => {
+                println!("{}", inventory_msg(&session.save));
+            }
+            "l" => {
+                println!("{}", history_msg(&session.save, None));
+            }
+            "s" => {
+                println!(
+                    "{}",
+                    shop_msg(&banners, banner, &session.save, NAME_OF_TICKET)
+                );
+            }
+            "e" => {
+                break;
+            }
+            cmd if cmd.starts_with("l ") => {
+                let name = cmd[2..].trim();
+                match Rarity::from_name(name) {
+                    Some(rarity) => println!("{}", history_msg(&session.save, Some(rarity))),
+                    None => println!(
+                        "{}",
+                        coloring_str(format!("No rarity named '{}'", name).as_str(), Color::Red)
+                    ),
+                }
+            }
+            cmd if cmd.starts_with("x ") => {
+                let path = cmd[2..].trim();
+                match export_history(&session.save, path) {
+                    Ok(()) => println!(
+                        "Exported {} pull(s) to '{}'.",
+                        session.save.history.len(),
+                        path
+                    ),
+                    Err(e) => println!(
                         "{}",
                         coloring_str(
-                            format!("You dont have enough {}s", NAME_OF_TICKET).as_str(),
+                            format!("Unable to export history: {}", e).as_str(),
                             Color::Red
                         )
-                    );
+                    ),
                 }
             }
-            "e" => {
-                break 'game;
-            }
-            _ => {
-                println!("Not a valid option, try again.");
-            }
+            cmd => match Action::parse(cmd) {
+                Ok(Some(action)) => {
+                    let single_pull = action == Action::Pull(1);
+                    match session.execute(action) {
+                        Ok(event) => {
+                            println!(
+                                "{}",
+                                event_msg(&event, &banners.economy.currency, NAME_OF_TICKET)
+                            );
+                            store(&session.save)?;
+                        }
+                        Err(e) if single_pull && e.is::<NotEnoughTickets>() => {
+                            println!(
+                                "{}",
+                                coloring_str(
+                                    format!("Sorry, you ran out of {}s", NAME_OF_TICKET).as_str(),
+                                    Color::Red
+                                )
+                            );
+                            break;
+                        }
+                        Err(e) => println!("{}", coloring_str(&e.to_string(), Color::Red)),
+                    }
+                }
+                Ok(None) => println!("Not a valid option, try again."),
+                Err(e) => println!("{}", coloring_str(&e.to_string(), Color::Red)),
+            },
         }
     }
+
+    store(&session.save)
 }
@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Pity model of a banner, which raises the odds of getting a Legendary item
+/// the longer the player went without one.
+#[derive(Debug, Deserialize)]
+pub struct Pity {
+    /// Number of pulls after which the Legendary rate starts to ramp up.
+    pub soft: u32,
+    /// The pull number that always gives a Legendary item.
+    pub hard: u32,
+    /// How much the Legendary rate increases on each pull past soft pity.
+    #[serde(default)]
+    pub soft_step: f64,
+    /// Featured Legendary items, they must also appear in the Legendary pool.
+    #[serde(default)]
+    pub featured: Vec<String>,
+    /// Chance of a Legendary item being a featured one when it's not guaranteed.
+    #[serde(default = "default_featured_chance")]
+    pub featured_chance: f64,
+}
+
+fn default_featured_chance() -> f64 {
+    0.5
+}
+
+/// Pity progress of a player on one banner.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PityCounter {
+    /// Pulls done since the last Legendary item.
+    pub pulls: u32,
+    /// Whether the next Legendary item is guaranteed to be a featured one,
+    /// which happens after losing a 50/50.
+    pub guaranteed_featured: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PlayerState {
+    pity: HashMap<String, PityCounter>,
+}
+
+impl PlayerState {
+    /// Get the pity counter of a banner, or a fresh one if the player never pulled on it.
+    pub fn pity(&self, banner: &str) -> PityCounter {
+        self.pity.get(banner).cloned().unwrap_or_default()
+    }
+
+    pub fn pity_mut(&mut self, banner: &str) -> &mut PityCounter {
+        self.pity.entry(banner.to_string()).or_default()
+    }
+}
+
+impl Pity {
+    /// Legendary rate of the `nth` pull since the last Legendary item, counting from 1.
+    pub fn legendary_rate(&self, base: f64, nth: u32) -> f64 {
+        if nth >= self.hard {
+            1.0
+        } else if nth > self.soft {
+            (base + (nth - self.soft) as f64 * self.soft_step).min(1.0)
+        } else {
+            base
+        }
+    }
+
+    /// Decide whether a Legendary item should come from the featured pool, and update
+    /// the featured guarantee of the counter accordingly.
+    ///
+    /// `roll` is a float between 0 and 1 which decides the 50/50 when there is no guarantee.
+    pub fn roll_featured(&self, counter: &mut PityCounter, roll: f64) -> bool {
+        if self.featured.is_empty() {
+            return false;
+        }
+        if counter.guaranteed_featured || roll < self.featured_chance {
+            counter.guaranteed_featured = false;
+            true
+        } else {
+            counter.guaranteed_featured = true;
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod pity_tests {
+    use super::{Pity, PityCounter};
+
+    fn pity() -> Pity {
+        Pity {
+            soft: 5,
+            hard: 10,
+            soft_step: 0.1,
+            featured: vec!["Mona".to_string()],
+            featured_chance: 0.5,
+        }
+    }
+
+    #[test]
+    fn soft_pity_ramps_up() {
+        let pity = pity();
+        assert_eq!(pity.legendary_rate(0.05, 1), 0.05);
+        assert_eq!(pity.legendary_rate(0.05, 5), 0.05);
+        assert!((pity.legendary_rate(0.05, 6) - 0.15).abs() < 1e-9);
+        assert!((pity.legendary_rate(0.05, 9) - 0.45).abs() < 1e-9);
+    }
+
+    #[test]
+    fn hard_pity_guarantees() {
+        let pity = pity();
+        assert_eq!(pity.legendary_rate(0.05, 10), 1.0);
+        assert_eq!(pity.legendary_rate(0.05, 11), 1.0);
+    }
+
+    #[test]
+    fn soft_pity_never_exceeds_one() {
+        let pity = Pity {
+            soft_step: 0.5,
+            ..pity()
+        };
+        assert_eq!(pity.legendary_rate(0.05, 9), 1.0);
+    }
+
+    #[test]
+    fn lost_fifty_fifty_guarantees_next() {
+        let pity = pity();
+        let mut counter = PityCounter::default();
+
+        // lose the 50/50
+        assert!(!pity.roll_featured(&mut counter, 0.9));
+        assert!(counter.guaranteed_featured);
+        // the next one is featured no matter what
+        assert!(pity.roll_featured(&mut counter, 0.9));
+        assert!(!counter.guaranteed_featured);
+        // win the 50/50 normally
+        assert!(pity.roll_featured(&mut counter, 0.1));
+        assert!(!counter.guaranteed_featured);
+    }
+
+    #[test]
+    fn no_featured_items() {
+        let pity = Pity {
+            featured: vec![],
+            ..pity()
+        };
+        let mut counter = PityCounter::default();
+        assert!(!pity.roll_featured(&mut counter, 0.0));
+        assert!(!counter.guaranteed_featured);
+    }
+}
@@ -1,44 +1,98 @@
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// A random number generator that can be seeded, so that the same seed
+/// always produces the same sequence of numbers.
+///
+/// `ChaCha8Rng` was used instead of `StdRng` because its output is guaranteed to
+/// stay the same across `rand` versions.
+pub struct Random {
+    rng: ChaCha8Rng,
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[allow(dead_code)]
-pub mod random {
-    use rand::seq::SliceRandom;
-    use rand::{thread_rng, Rng};
+impl Random {
+    /// Create a generator seeded from system entropy.
+    pub fn new() -> Self {
+        Random {
+            rng: ChaCha8Rng::from_entropy(),
+        }
+    }
+
+    /// Create a generator with a fixed seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Random {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
 
     /// Generate a float number between 0 and 1.
-    pub fn float() -> f64 {
-        thread_rng().gen_range(0.0..1.0)
+    pub fn float(&mut self) -> f64 {
+        self.rng.gen_range(0.0..1.0)
     }
 
     /// Generate a float number within specific range.
-    pub fn float_in_range(min: f64, max: f64) -> f64 {
+    pub fn float_in_range(&mut self, min: f64, max: f64) -> f64 {
         if min > max {
             eprintln!(
-                "Invalid argument at Random.float_in_range(min, max). 
+                "Invalid argument at Random.float_in_range(min, max).
                 min value cannot be greater than max value. "
             );
             return 0.0;
         }
-        thread_rng().gen_range(min..max)
+        self.rng.gen_range(min..max)
     }
 
     /// Generate an integer bounded by the size of a i32.
-    pub fn integer() -> i32 {
-        thread_rng().gen::<i32>()
+    pub fn integer(&mut self) -> i32 {
+        self.rng.gen::<i32>()
     }
 
     /// Generate an interger with specific range.
-    pub fn integer_in_range(min: i32, max: i32) -> i32 {
+    pub fn integer_in_range(&mut self, min: i32, max: i32) -> i32 {
         if min > max {
             eprintln!(
-                "Invalid argument at Random.integer_in_range(min, max). 
+                "Invalid argument at Random.integer_in_range(min, max).
                 min value cannot be greater than max value. "
             );
             return 0;
         }
-        thread_rng().gen_range(min..max)
+        self.rng.gen_range(min..max)
     }
 
     /// Return a random element in slice.
-    pub fn element_in_slice<T>(list: &[T]) -> Option<&T> {
-        list.choose(&mut thread_rng())
+    pub fn element_in_slice<'a, T>(&mut self, list: &'a [T]) -> Option<&'a T> {
+        list.choose(&mut self.rng)
+    }
+}
+
+#[cfg(test)]
+mod rng_tests {
+    use super::Random;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut a = Random::with_seed(7);
+        let mut b = Random::with_seed(7);
+        for _ in 0..100 {
+            assert_eq!(a.float(), b.float());
+            assert_eq!(a.integer_in_range(0, 10), b.integer_in_range(0, 10));
+        }
+    }
+
+    #[test]
+    fn different_seed_different_sequence() {
+        let mut a = Random::with_seed(1);
+        let mut b = Random::with_seed(2);
+        let seq_a: Vec<i32> = (0..10).map(|_| a.integer()).collect();
+        let seq_b: Vec<i32> = (0..10).map(|_| b.integer()).collect();
+        assert_ne!(seq_a, seq_b);
     }
 }
@@ -0,0 +1,285 @@
+use crate::item::Rarity;
+use crate::pity::PlayerState;
+use crate::wallet::Wallet;
+use crate::Pull;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+use time::macros::format_description;
+use time::OffsetDateTime;
+
+/// Default location of the save file, relative to the working directory.
+pub const DEFAULT_SAVE_FILE: &str = "gotcha_save.json";
+
+/// Tickets that a brand new player starts with.
+const STARTING_TICKETS: u32 = 100;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaveData {
+    #[serde(flatten)]
+    pub wallet: Wallet,
+    /// Secondary currency earned by converting duplicates.
+    #[serde(default)]
+    pub currency: u32,
+    #[serde(default)]
+    pub history: Vec<PullRecord>,
+    #[serde(default)]
+    pub inventory: Vec<InventoryEntry>,
+    #[serde(default)]
+    pub state: PlayerState,
+}
+
+/// A single pull in history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PullRecord {
+    /// Unix timestamp in seconds.
+    pub timestamp: i64,
+    pub banner: String,
+    pub item: String,
+    pub rarity: Rarity,
+    pub refunded: bool,
+}
+
+/// Owned copies of an item, items with the same name but different rarity
+/// (from different banners) are kept separately.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InventoryEntry {
+    pub name: String,
+    pub rarity: Rarity,
+    pub count: u32,
+}
+
+impl Default for SaveData {
+    fn default() -> Self {
+        SaveData {
+            wallet: Wallet::new(STARTING_TICKETS),
+            currency: 0,
+            history: vec![],
+            inventory: vec![],
+            state: PlayerState::default(),
+        }
+    }
+}
+
+impl SaveData {
+    /// Load the save file, or start a new game if there isn't one.
+    pub fn load_or_new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("unable to read save file '{}': {}", path.display(), e))?;
+        serde_json::from_str(&raw)
+            .map_err(|e| anyhow!("corrupted save file '{}': {}", path.display(), e))
+    }
+
+    pub fn store<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .map_err(|e| anyhow!("unable to write save file '{}': {}", path.display(), e))
+    }
+
+    /// Record pulls from a banner into history and inventory.
+    pub fn record(&mut self, banner: &str, pulls: &[Pull]) {
+        let timestamp = OffsetDateTime::now_utc().unix_timestamp();
+        for pull in pulls {
+            self.history.push(PullRecord {
+                timestamp,
+                banner: banner.to_string(),
+                item: pull.item.name.clone(),
+                rarity: pull.item.rarity,
+                refunded: pull.refunded,
+            });
+            self.add_item(&pull.item.name, pull.item.rarity);
+        }
+    }
+
+    pub fn add_item(&mut self, name: &str, rarity: Rarity) {
+        match self
+            .inventory
+            .iter_mut()
+            .find(|e| e.name == name && e.rarity == rarity)
+        {
+            Some(entry) => entry.count = entry.count.saturating_add(1),
+            None => self.inventory.push(InventoryEntry {
+                name: name.to_string(),
+                rarity,
+                count: 1,
+            }),
+        }
+    }
+
+    /// Remove `amount` copies of an item, the entry will be gone if there's none left.
+    pub fn remove_item(&mut self, name: &str, rarity: Rarity, amount: u32) {
+        if let Some(idx) = self
+            .inventory
+            .iter()
+            .position(|e| e.name == name && e.rarity == rarity)
+        {
+            let entry = &mut self.inventory[idx];
+            entry.count = entry.count.saturating_sub(amount);
+            if entry.count == 0 {
+                self.inventory.remove(idx);
+            }
+        }
+    }
+
+    /// Number of copies owned of an item.
+    pub fn count_of(&self, name: &str, rarity: Rarity) -> u32 {
+        self.inventory
+            .iter()
+            .find(|e| e.name == name && e.rarity == rarity)
+            .map_or(0, |e| e.count)
+    }
+
+    /// Inventory sorted from the rarest item, then by name.
+    pub fn sorted_inventory(&self) -> Vec<&InventoryEntry> {
+        let mut entries: Vec<&InventoryEntry> = self.inventory.iter().collect();
+        entries.sort_by(|a, b| a.rarity.cmp(&b.rarity).then_with(|| a.name.cmp(&b.name)));
+        entries
+    }
+
+    /// Pull history, optionally only the ones of a specific rarity.
+    pub fn history_of(&self, rarity: Option<Rarity>) -> impl Iterator<Item = &PullRecord> {
+        self.history
+            .iter()
+            .filter(move |r| rarity.is_none_or(|rarity| r.rarity == rarity))
+    }
+
+    /// Write the whole pull history as CSV.
+    pub fn export_csv<W: Write>(&self, mut writer: W) -> Result<()> {
+        writeln!(writer, "timestamp,banner,item,rarity,refunded")?;
+        for record in &self.history {
+            writeln!(
+                writer,
+                "{},{},{},{},{}",
+                format_timestamp(record.timestamp),
+                csv_field(&record.banner),
+                csv_field(&record.item),
+                record.rarity.stringify(),
+                record.refunded
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Format a unix timestamp as UTC date time, e.g. `2022-01-31 13:01:59`.
+pub fn format_timestamp(timestamp: i64) -> String {
+    OffsetDateTime::from_unix_timestamp(timestamp)
+        .ok()
+        .and_then(|dt| {
+            dt.format(format_description!(
+                "[year]-[month]-[day] [hour]:[minute]:[second]"
+            ))
+            .ok()
+        })
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+/// Quote a CSV field if it contains any special character.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod save_tests {
+    use super::{csv_field, format_timestamp, SaveData};
+    use crate::item::{Item, Rarity};
+    use crate::Pull;
+
+    fn pulled(name: &str, rarity: Rarity, refunded: bool) -> Pull {
+        Pull {
+            item: Item {
+                name: name.to_string(),
+                rarity,
+            },
+            refunded,
+        }
+    }
+
+    fn sample() -> SaveData {
+        let mut save = SaveData::default();
+        save.record(
+            "Standard",
+            &[
+                pulled("Sword", Rarity::Normal, false),
+                pulled("Mona", Rarity::Legendary, true),
+                pulled("Sword", Rarity::Normal, false),
+            ],
+        );
+        save.record("Armory", &[pulled("Black Sword", Rarity::Legendary, false)]);
+        save
+    }
+
+    #[test]
+    fn stack_duplicates() {
+        let save = sample();
+        assert_eq!(save.history.len(), 4);
+
+        let inventory: Vec<(&str, u32)> = save
+            .sorted_inventory()
+            .into_iter()
+            .map(|e| (e.name.as_str(), e.count))
+            .collect();
+        assert_eq!(inventory, [("Black Sword", 1), ("Mona", 1), ("Sword", 2)]);
+    }
+
+    #[test]
+    fn stack_stops_at_max() {
+        let mut save = SaveData::default();
+        save.add_item("Sword", Rarity::Normal);
+        save.inventory[0].count = u32::MAX;
+        save.record("Standard", &[pulled("Sword", Rarity::Normal, false)]);
+        assert_eq!(save.inventory[0].count, u32::MAX);
+        assert_eq!(save.history.len(), 1);
+    }
+
+    #[test]
+    fn filter_history() {
+        let save = sample();
+        assert_eq!(save.history_of(None).count(), 4);
+        assert_eq!(save.history_of(Some(Rarity::Legendary)).count(), 2);
+        assert_eq!(save.history_of(Some(Rarity::Epic)).count(), 0);
+    }
+
+    #[test]
+    fn round_trip() {
+        let save = sample();
+        let json = serde_json::to_string(&save).unwrap();
+        let loaded: SaveData = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.wallet, save.wallet);
+        assert_eq!(loaded.history, save.history);
+        assert_eq!(loaded.inventory, save.inventory);
+    }
+
+    #[test]
+    fn export_csv() {
+        let save = sample();
+        let mut out = vec![];
+        save.export_csv(&mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "timestamp,banner,item,rarity,refunded");
+        assert!(lines[2].ends_with(",Standard,Mona,Legendary,true"));
+    }
+
+    #[test]
+    fn escape_csv_field() {
+        assert_eq!(csv_field("Sword"), "Sword");
+        assert_eq!(csv_field("Sword, big"), "\"Sword, big\"");
+        assert_eq!(csv_field("The \"Sword\""), "\"The \"\"Sword\"\"\"");
+    }
+
+    #[test]
+    fn timestamp_format() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00");
+    }
+}
@@ -0,0 +1,313 @@
+use crate::banner::{Banner, BannerCfg};
+use crate::item::Item;
+use crate::paid_pull;
+use crate::rng_helper::Random;
+use crate::save::SaveData;
+use crate::wallet::Pricing;
+use crate::Pull;
+use anyhow::{anyhow, bail, Result};
+use serde::Serialize;
+use std::fmt;
+
+/// A command that changes the game, shared by the interactive prompt and batch mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Pull a number of times at once.
+    Pull(u8),
+    /// Switch to a banner by its number (counting from 1) or name.
+    Switch(String),
+    /// Convert every duplicate into currency.
+    ConvertAll,
+    /// Convert one copy of an item into currency.
+    Convert(String),
+    BuyTickets(u32),
+    BuyItem(String),
+}
+
+impl Action {
+    /// Parse a command as entered in game, e.g. `10`, `b Armory` or `s ticket 5`.
+    ///
+    /// An empty command is a single pull, `pull <COUNT>` pulls any number of times at once.
+    /// Returns `None` if the command doesn't change the game (like showing the inventory).
+    pub fn parse(cmd: &str) -> Result<Option<Action>> {
+        let cmd = cmd.trim();
+        let (name, arg) = match cmd.split_once(' ') {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (cmd, ""),
+        };
+        let action = match (name, arg) {
+            ("", _) | ("pull", "") => Action::Pull(1),
+            ("10", "") => Action::Pull(10),
+            ("pull", count) => Action::Pull(
+                count
+                    .parse()
+                    .ok()
+                    .filter(|count| *count > 0)
+                    .ok_or_else(|| anyhow!("'{}' is not a valid count", count))?,
+            ),
+            ("b", target) if !target.is_empty() => Action::Switch(target.to_string()),
+            ("c", "") => Action::ConvertAll,
+            ("c", name) => Action::Convert(name.to_string()),
+            ("s", option) if !option.is_empty() => match option.split_once(' ') {
+                Some(("ticket", count)) => Action::BuyTickets(
+                    count
+                        .trim()
+                        .parse()
+                        .map_err(|_| anyhow!("'{}' is not a valid count", count.trim()))?,
+                ),
+                Some(("item", name)) => Action::BuyItem(name.trim().to_string()),
+                _ => bail!("Not a valid shop option, try again."),
+            },
+            _ => return Ok(None),
+        };
+        Ok(Some(action))
+    }
+
+    /// Split `pulls` pulls into as many discounted multi-pulls as possible,
+    /// the rest are done one by one.
+    pub fn pulls(pulls: u32, pricing: &Pricing) -> Vec<Action> {
+        let multi = pricing.multi_pulls.clamp(1, u32::from(u8::MAX));
+        let mut actions = vec![Action::Pull(multi as u8); (pulls / multi) as usize];
+        actions.extend(vec![Action::Pull(1); (pulls % multi) as usize]);
+        actions
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Action::Pull(count) => write!(f, "pull {}", count),
+            Action::Switch(target) => write!(f, "b {}", target),
+            Action::ConvertAll => write!(f, "c"),
+            Action::Convert(name) => write!(f, "c {}", name),
+            Action::BuyTickets(count) => write!(f, "s ticket {}", count),
+            Action::BuyItem(name) => write!(f, "s item {}", name),
+        }
+    }
+}
+
+/// What happened after an [`Action`].
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    Pulled {
+        banner: String,
+        items: Vec<Pull>,
+        /// Tickets given back from refunded pulls.
+        refund: u32,
+    },
+    Switched {
+        banner: String,
+    },
+    Converted {
+        copies: u32,
+        earned: u32,
+    },
+    BoughtTickets {
+        count: u32,
+        cost: u32,
+    },
+    BoughtItem {
+        item: Item,
+        cost: u32,
+    },
+}
+
+/// A game in progress: the player's save, the random generator and the banner being pulled on.
+pub struct Session<'a> {
+    pub cfg: &'a BannerCfg,
+    pub save: SaveData,
+    pub rng: Random,
+    banner_idx: usize,
+}
+
+impl<'a> Session<'a> {
+    /// Start on the first banner.
+    pub fn new(cfg: &'a BannerCfg, save: SaveData, rng: Random) -> Self {
+        Session {
+            cfg,
+            save,
+            rng,
+            banner_idx: 0,
+        }
+    }
+
+    pub fn banner(&self) -> &'a Banner {
+        &self.cfg.banners[self.banner_idx]
+    }
+
+    pub fn banner_idx(&self) -> usize {
+        self.banner_idx
+    }
+
+    /// Perform an action, nothing is changed if it fails.
+    pub fn execute(&mut self, action: Action) -> Result<Event> {
+        let cfg = self.cfg;
+        let banner = self.banner();
+        let economy = &cfg.economy;
+        let event = match action {
+            Action::Pull(count) => {
+                let (items, refund) = paid_pull(
+                    banner,
+                    &mut self.save.state,
+                    &mut self.rng,
+                    &mut self.save.wallet,
+                    &economy.pricing,
+                    count,
+                )?;
+                self.save.record(&banner.name, &items);
+                Event::Pulled {
+                    banner: banner.name.clone(),
+                    items,
+                    refund,
+                }
+            }
+            Action::Switch(target) => {
+                let selected = match target.parse::<usize>() {
+                    Ok(num) if num >= 1 && num <= cfg.banners.len() => Some(num - 1),
+                    _ => cfg.position(&target),
+                };
+                self.banner_idx =
+                    selected.ok_or_else(|| anyhow!("No banner named '{}'", target))?;
+                Event::Switched {
+                    banner: self.banner().name.clone(),
+                }
+            }
+            Action::ConvertAll => {
+                let (copies, earned) = economy.convert_duplicates(&mut self.save);
+                Event::Converted { copies, earned }
+            }
+            Action::Convert(name) => {
+                // convert the copy of the item that has the most duplicates
+                let (name, rarity) = self
+                    .save
+                    .sorted_inventory()
+                    .into_iter()
+                    .filter(|e| e.name.eq_ignore_ascii_case(&name))
+                    .max_by_key(|e| e.count)
+                    .map(|e| (e.name.clone(), e.rarity))
+                    .ok_or_else(|| anyhow!("you don't have any '{}'", name))?;
+                let earned = economy.convert(&mut self.save, &name, rarity, 1)?;
+                Event::Converted { copies: 1, earned }
+            }
+            Action::BuyTickets(count) => {
+                let cost = economy.buy_tickets(&mut self.save, count)?;
+                Event::BoughtTickets { count, cost }
+            }
+            Action::BuyItem(name) => {
+                let (name, rarity, cost) = economy.buy_item(&mut self.save, banner, &name)?;
+                Event::BoughtItem {
+                    item: Item { name, rarity },
+                    cost,
+                }
+            }
+        };
+        Ok(event)
+    }
+}
+
+#[cfg(test)]
+mod session_tests {
+    use super::{Action, Event, Session};
+    use crate::banner::BannerCfg;
+    use crate::item::Rarity;
+    use crate::rng_helper::Random;
+    use crate::save::SaveData;
+    use crate::wallet::Pricing;
+
+    const SAMPLE: &str = r#"
+    {
+        "banners": [
+            {
+                "name": "Test",
+                "tiers": [{ "rarity": "Normal", "rate": 1.0, "items": ["Sword"] }]
+            },
+            {
+                "name": "Armory",
+                "tiers": [{ "rarity": "Epic", "rate": 1.0, "items": ["Bow"] }]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn parse_commands() {
+        let parse = |cmd: &str| Action::parse(cmd).unwrap();
+        assert_eq!(parse(""), Some(Action::Pull(1)));
+        assert_eq!(parse("10"), Some(Action::Pull(10)));
+        assert_eq!(parse("pull 25"), Some(Action::Pull(25)));
+        assert_eq!(parse("b  armory "), Some(Action::Switch("armory".into())));
+        assert_eq!(parse("c"), Some(Action::ConvertAll));
+        assert_eq!(parse("s ticket 5"), Some(Action::BuyTickets(5)));
+        assert_eq!(
+            parse("s item Black Sword"),
+            Some(Action::BuyItem("Black Sword".into()))
+        );
+        assert_eq!(parse("i"), None);
+        assert_eq!(parse("b"), None);
+        assert!(Action::parse("pull 1000").is_err());
+        assert!(Action::parse("pull 0").is_err());
+        assert!(Action::parse("s ticket many").is_err());
+        assert!(Action::parse("s hat").is_err());
+
+        // commands can be written back the same way they are parsed
+        for action in [
+            Action::Pull(7),
+            Action::Convert("Mona".into()),
+            Action::BuyTickets(2),
+        ] {
+            assert_eq!(parse(&action.to_string()), Some(action));
+        }
+    }
+
+    #[test]
+    fn split_pulls() {
+        let pricing = Pricing::default();
+        assert_eq!(
+            Action::pulls(23, &pricing),
+            [
+                Action::Pull(10),
+                Action::Pull(10),
+                Action::Pull(1),
+                Action::Pull(1),
+                Action::Pull(1)
+            ]
+        );
+        assert!(Action::pulls(0, &pricing).is_empty());
+    }
+
+    #[test]
+    fn run_actions() {
+        let cfg = SAMPLE.parse::<BannerCfg>().unwrap();
+        let mut session = Session::new(&cfg, SaveData::default(), Random::with_seed(0));
+
+        match session.execute(Action::Pull(10)).unwrap() {
+            Event::Pulled { banner, items, .. } => {
+                assert_eq!(banner, "Test");
+                assert_eq!(items.len(), 10);
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+        assert_eq!(session.save.count_of("Sword", Rarity::Normal), 10);
+
+        assert!(session.execute(Action::Switch("Gear".into())).is_err());
+        assert_eq!(session.banner_idx(), 0);
+        session.execute(Action::Switch("2".into())).unwrap();
+        assert_eq!(session.banner().name, "Armory");
+
+        session.execute(Action::Convert("sword".into())).unwrap();
+        assert_eq!(session.save.count_of("Sword", Rarity::Normal), 9);
+    }
+
+    #[test]
+    fn event_json() {
+        let cfg = SAMPLE.parse::<BannerCfg>().unwrap();
+        let mut session = Session::new(&cfg, SaveData::default(), Random::with_seed(0));
+        let event = session.execute(Action::Pull(1)).unwrap();
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["event"], "pulled");
+        assert_eq!(json["banner"], "Test");
+        assert_eq!(json["items"][0]["name"], "Sword");
+        assert_eq!(json["items"][0]["rarity"], "Normal");
+        assert_eq!(json["items"][0]["refunded"], false);
+    }
+}
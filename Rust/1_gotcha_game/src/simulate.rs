@@ -0,0 +1,426 @@
+use crate::banner::{Banner, BannerCfg};
+use crate::item::{coloring_str, Color, Rarity};
+use crate::pity::PlayerState;
+use crate::rng_helper::Random;
+use crate::wallet::Pricing;
+use crate::{pull, Pull};
+use anyhow::{anyhow, bail, Result};
+use clap::ArgMatches;
+use std::collections::HashMap;
+use std::thread;
+
+/// z-score of a 95% confidence interval.
+const Z_95: f64 = 1.96;
+
+/// Outcome of a simulation run.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SimStats {
+    pub pulls: u64,
+    /// Number of pulls that got refunded.
+    pub refunds: u64,
+    /// Tickets paid for every pull, discounts included.
+    pub tickets_paid: u64,
+    /// Tickets given back by refunds.
+    pub tickets_refunded: u64,
+    pub rarity_count: HashMap<Rarity, u64>,
+    /// Number of Legendary items pulled.
+    pub legendaries: u64,
+    /// Sum of pulls it took to get each of the `legendaries`.
+    pub pulls_to_legendary: u64,
+    /// Longest streak of pulls it took to get a Legendary item.
+    pub longest_streak: u64,
+}
+
+impl SimStats {
+    fn merge(&mut self, other: SimStats) {
+        self.pulls += other.pulls;
+        self.refunds += other.refunds;
+        self.tickets_paid += other.tickets_paid;
+        self.tickets_refunded += other.tickets_refunded;
+        for (rarity, count) in other.rarity_count {
+            *self.rarity_count.entry(rarity).or_default() += count;
+        }
+        self.legendaries += other.legendaries;
+        self.pulls_to_legendary += other.pulls_to_legendary;
+        self.longest_streak = self.longest_streak.max(other.longest_streak);
+    }
+
+    pub fn observed_rate(&self, rarity: Rarity) -> f64 {
+        if self.pulls == 0 {
+            return 0.0;
+        }
+        self.rarity_count.get(&rarity).copied().unwrap_or_default() as f64 / self.pulls as f64
+    }
+
+    /// 95% confidence interval of the observed rate of a rarity.
+    pub fn confidence_interval(&self, rarity: Rarity) -> (f64, f64) {
+        let p = self.observed_rate(rarity);
+        let margin = Z_95 * (p * (1.0 - p) / self.pulls.max(1) as f64).sqrt();
+        ((p - margin).max(0.0), (p + margin).min(1.0))
+    }
+}
+
+/// A tier whose observed rate is too far away from its expected rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Deviation {
+    pub rarity: Rarity,
+    pub expected: f64,
+    pub observed: f64,
+}
+
+/// Average number of pulls needed to get a Legendary item from a banner, pity included.
+///
+/// Returns `None` if the banner can never give a Legendary item.
+pub fn expected_pulls_to_legendary(banner: &Banner) -> Option<f64> {
+    let base = banner.legendary_tier()?.rate;
+    let pity = match &banner.pity {
+        Some(pity) => pity,
+        None if base > 0.0 => return Some(1.0 / base),
+        None => return None,
+    };
+
+    // sum up the chance of reaching the `nth` pull without a Legendary item,
+    // since hard pity ends every streak, this is a finite sum.
+    let mut expected = 0.0;
+    let mut no_legendary_yet = 1.0;
+    for nth in 1..=pity.hard {
+        expected += no_legendary_yet;
+        no_legendary_yet *= 1.0 - pity.legendary_rate(base, nth);
+    }
+    Some(expected)
+}
+
+/// Long run rate of each tier of a banner.
+///
+/// Without pity these are the configured rates, with pity the Legendary rate
+/// gets higher and the other tiers share the rest by their configured proportions.
+pub fn expected_rates(banner: &Banner) -> Vec<(Rarity, f64)> {
+    let (base, consolidated) = match (banner.legendary_tier(), &banner.pity) {
+        (Some(legendary), Some(_)) => (
+            legendary.rate,
+            expected_pulls_to_legendary(banner).map_or(0.0, |e| 1.0 / e),
+        ),
+        _ => return banner.tiers.iter().map(|t| (t.rarity, t.rate)).collect(),
+    };
+
+    banner
+        .tiers
+        .iter()
+        .map(|t| {
+            if t.rarity == Rarity::Legendary {
+                (t.rarity, consolidated)
+            } else if base >= 1.0 {
+                // nothing is left for the other tiers, and their share would be 0 / 0
+                (t.rarity, 0.0)
+            } else {
+                (t.rarity, (1.0 - consolidated) * t.rate / (1.0 - base))
+            }
+        })
+        .collect()
+}
+
+/// Pull `pulls` times on a banner, spread across `threads` threads.
+///
+/// Pulls are done in multi-pulls whenever possible, so their tickets are paid with the discount.
+/// Each thread gets its own generator seeded with `seed` plus the thread index,
+/// so a run is reproducible as long as the seed and thread count stays the same.
+pub fn run(banner: &Banner, pricing: &Pricing, pulls: u64, threads: usize, seed: u64) -> SimStats {
+    let threads = threads.max(1) as u64;
+    thread::scope(|s| {
+        let handles: Vec<_> = (0..threads)
+            .map(|i| {
+                // first few threads take the remainder
+                let share = pulls / threads + u64::from(i < pulls % threads);
+                s.spawn(move || run_single(banner, pricing, share, seed.wrapping_add(i)))
+            })
+            .collect();
+
+        let mut stats = SimStats::default();
+        for handle in handles {
+            stats.merge(handle.join().expect("simulation thread panicked"));
+        }
+        stats
+    })
+}
+
+fn run_single(banner: &Banner, pricing: &Pricing, pulls: u64, seed: u64) -> SimStats {
+    let batch = u64::from(pricing.multi_pulls.clamp(1, u8::MAX as u32));
+    let mut stats = SimStats::default();
+    let mut state = PlayerState::default();
+    let mut rng = Random::with_seed(seed);
+    let mut streak: u64 = 0;
+
+    let mut remaining = pulls;
+    while remaining > 0 {
+        let count = remaining.min(batch);
+        let pulls = pull(banner, &mut state, &mut rng, count as u8);
+        remaining -= count;
+
+        let cost = pricing.cost_of(count as u32);
+        let refunded = pulls.iter().filter(|p| p.refunded).count() as u32;
+        stats.pulls += count;
+        stats.tickets_paid += u64::from(cost);
+        stats.tickets_refunded += u64::from(pricing.refund_of(cost, refunded));
+        for Pull { item, refunded } in pulls {
+            stats.refunds += u64::from(refunded);
+            *stats.rarity_count.entry(item.rarity).or_default() += 1;
+            streak += 1;
+            if item.rarity == Rarity::Legendary {
+                stats.legendaries += 1;
+                stats.pulls_to_legendary += streak;
+                stats.longest_streak = stats.longest_streak.max(streak);
+                streak = 0;
+            }
+        }
+    }
+    stats
+}
+
+/// Compare the observed rates with the expected ones, and return every tier
+/// deviating more than `tolerance`.
+pub fn deviations(banner: &Banner, stats: &SimStats, tolerance: f64) -> Vec<Deviation> {
+    expected_rates(banner)
+        .into_iter()
+        .map(|(rarity, expected)| Deviation {
+            rarity,
+            expected,
+            observed: stats.observed_rate(rarity),
+        })
+        .filter(|d| (d.observed - d.expected).abs() > tolerance)
+        .collect()
+}
+
+fn percent(rate: f64) -> String {
+    format!("{:.3}%", rate * 100.0)
+}
+
+fn report(banner: &Banner, stats: &SimStats, deviations: &[Deviation], tolerance: f64) -> String {
+    let mut msg = format!(
+        "{:<12}{:>12}{:>12}{:>26}\n",
+        "Rarity", "Expected", "Observed", "95% CI"
+    );
+    for (rarity, expected) in expected_rates(banner) {
+        let (low, high) = stats.confidence_interval(rarity);
+        let line = format!(
+            "{:<12}{:>12}{:>12}{:>26}",
+            rarity.stringify(),
+            percent(expected),
+            percent(stats.observed_rate(rarity)),
+            format!("[{}, {}]", percent(low), percent(high)),
+        );
+        if deviations.iter().any(|d| d.rarity == rarity) {
+            msg.push_str(&coloring_str(&line, Color::Red));
+        } else {
+            msg.push_str(&line);
+        }
+        msg.push('\n');
+    }
+
+    if stats.legendaries > 0 {
+        msg.push_str(&format!(
+            "\nAverage pulls to {}: {:.2}",
+            Rarity::Legendary.stringify(),
+            stats.pulls_to_legendary as f64 / stats.legendaries as f64
+        ));
+        if let Some(expected) = expected_pulls_to_legendary(banner) {
+            msg.push_str(&format!(" (expected {:.2})", expected));
+        }
+        msg.push_str(&format!(", longest streak: {}", stats.longest_streak));
+    }
+
+    let net = stats.tickets_paid - stats.tickets_refunded;
+    msg.push_str(&format!(
+        "\nTickets: {} paid, {} refunded from {} refunded pulls, {} net ({:.4} per pull",
+        stats.tickets_paid,
+        stats.tickets_refunded,
+        stats.refunds,
+        net,
+        net as f64 / stats.pulls.max(1) as f64
+    ));
+    if stats.legendaries > 0 {
+        msg.push_str(&format!(
+            ", {:.2} per {}",
+            net as f64 / stats.legendaries as f64,
+            Rarity::Legendary.stringify()
+        ));
+    }
+    msg.push_str(")\n");
+
+    for d in deviations {
+        msg.push_str(&coloring_str(
+            &format!(
+                "\n{} deviates from its expected rate: {} observed, {} expected (tolerance {})",
+                d.rarity.stringify(),
+                percent(d.observed),
+                percent(d.expected),
+                percent(tolerance)
+            ),
+            Color::Red,
+        ));
+    }
+    msg
+}
+
+/// Entry of the `simulate` subcommand.
+pub fn simulate(am: &ArgMatches, banners: &BannerCfg, seed: Option<u64>) -> Result<()> {
+    let banner = match am.get_one::<String>("banner") {
+        Some(name) => {
+            let idx = banners
+                .position(name)
+                .ok_or_else(|| anyhow!("no banner named '{}'", name))?;
+            &banners.banners[idx]
+        }
+        None => &banners.banners[0],
+    };
+    let pulls = *am
+        .get_one::<u64>("pulls")
+        .expect("pulls has a default value");
+    let tolerance = *am
+        .get_one::<f64>("tolerance")
+        .expect("tolerance has a default value");
+    let threads = match am.get_one::<usize>("threads") {
+        Some(threads) => *threads,
+        None => thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+    let seed = seed.unwrap_or_else(rand::random);
+
+    println!(
+        "Simulating {} pulls on banner \"{}\" with {} thread(s), seed {}...\n",
+        pulls, banner.name, threads, seed
+    );
+    let stats = run(banner, &banners.economy.pricing, pulls, threads, seed);
+    let deviations = deviations(banner, &stats, tolerance);
+    println!("{}", report(banner, &stats, &deviations, tolerance));
+
+    if !deviations.is_empty() {
+        bail!(
+            "{} tier(s) deviate from their expected rates",
+            deviations.len()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod simulate_tests {
+    use super::{deviations, expected_pulls_to_legendary, expected_rates, run};
+    use crate::banner::BannerCfg;
+    use crate::item::Rarity;
+    use crate::wallet::Pricing;
+
+    const SAMPLE: &str = r#"
+    {
+        "banners": [
+            {
+                "name": "Plain",
+                "refund_chance": 0.5,
+                "tiers": [
+                    { "rarity": "Legendary", "rate": 0.1, "items": ["Mona"] },
+                    { "rarity": "Epic", "rate": 0.3, "items": ["Amber"] },
+                    { "rarity": "Normal", "rate": 0.6, "items": ["Sword"] }
+                ]
+            },
+            {
+                "name": "Pity",
+                "tiers": [
+                    { "rarity": "Legendary", "rate": 0.05, "items": ["Mona"] },
+                    { "rarity": "Epic", "rate": 0.15, "items": ["Amber"] },
+                    { "rarity": "Normal", "rate": 0.8, "items": ["Sword"] }
+                ],
+                "pity": { "soft": 10, "hard": 20, "soft_step": 0.1 }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn expected_without_pity() {
+        let cfg = SAMPLE.parse::<BannerCfg>().unwrap();
+        let banner = &cfg.banners[0];
+        assert_eq!(
+            expected_rates(banner),
+            [
+                (Rarity::Legendary, 0.1),
+                (Rarity::Epic, 0.3),
+                (Rarity::Normal, 0.6)
+            ]
+        );
+        assert!((expected_pulls_to_legendary(banner).unwrap() - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn expected_with_pity() {
+        let cfg = SAMPLE.parse::<BannerCfg>().unwrap();
+        let banner = &cfg.banners[1];
+        let expected = expected_pulls_to_legendary(banner).unwrap();
+        assert!(expected > 1.0 && expected < 20.0);
+
+        let rates = expected_rates(banner);
+        let sum: f64 = rates.iter().map(|(_, r)| r).sum();
+        assert!((sum - 1.0).abs() < 1e-9);
+        assert!((rates[0].1 - 1.0 / expected).abs() < 1e-9);
+        // Epic and Normal keep their 15:80 proportion
+        assert!((rates[1].1 / rates[2].1 - 0.15 / 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn expected_with_certain_legendary() {
+        let src = SAMPLE
+            .replace("\"rate\": 0.05", "\"rate\": 1.0")
+            .replace("\"rate\": 0.15", "\"rate\": 0.0")
+            .replace("\"rate\": 0.8", "\"rate\": 0.0");
+        let cfg = src.parse::<BannerCfg>().unwrap();
+        let rates = expected_rates(&cfg.banners[1]);
+        assert_eq!(
+            rates,
+            [
+                (Rarity::Legendary, 1.0),
+                (Rarity::Epic, 0.0),
+                (Rarity::Normal, 0.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn simulation_matches_config() {
+        let cfg = SAMPLE.parse::<BannerCfg>().unwrap();
+        for banner in &cfg.banners {
+            let stats = run(banner, &Pricing::default(), 200_000, 4, 42);
+            assert_eq!(stats.pulls, 200_000);
+            assert!(deviations(banner, &stats, 0.005).is_empty());
+            assert!(
+                stats.longest_streak <= banner.pity.as_ref().map_or(u64::MAX, |p| p.hard as u64)
+            );
+        }
+    }
+
+    #[test]
+    fn simulation_is_reproducible() {
+        let cfg = SAMPLE.parse::<BannerCfg>().unwrap();
+        let banner = &cfg.banners[0];
+        let pricing = Pricing::default();
+        assert_eq!(
+            run(banner, &pricing, 10_001, 3, 7),
+            run(banner, &pricing, 10_001, 3, 7)
+        );
+    }
+
+    #[test]
+    fn ticket_consumption() {
+        let cfg = SAMPLE.parse::<BannerCfg>().unwrap();
+        let pricing = Pricing::default();
+        let stats = run(&cfg.banners[0], &pricing, 1_000, 1, 3);
+        // 100 multi-pulls with 9 tickets each
+        assert_eq!(stats.tickets_paid, 900);
+        assert!(stats.tickets_refunded <= stats.refunds);
+        assert!(stats.tickets_refunded <= stats.tickets_paid);
+    }
+
+    #[test]
+    fn flag_deviations() {
+        let cfg = SAMPLE.parse::<BannerCfg>().unwrap();
+        let stats = run(&cfg.banners[0], &Pricing::default(), 10_000, 2, 1);
+        // pretend the advertised rates were from the other banner
+        let flagged = deviations(&cfg.banners[1], &stats, 0.01);
+        assert!(flagged.iter().any(|d| d.rarity == Rarity::Epic));
+    }
+}
@@ -0,0 +1,234 @@
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How many tickets pulls cost.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Pricing {
+    /// Number of pulls that has to be done at once to get the discount.
+    pub multi_pulls: u32,
+    /// Tickets that a discounted multi-pull costs.
+    pub multi_cost: u32,
+}
+
+impl Default for Pricing {
+    fn default() -> Self {
+        Pricing {
+            multi_pulls: 10,
+            multi_cost: 9,
+        }
+    }
+}
+
+impl Pricing {
+    pub fn validate(&self) -> Result<()> {
+        if self.multi_pulls == 0 || self.multi_cost == 0 || self.multi_cost > self.multi_pulls {
+            bail!(
+                "pricing: multi_cost ({}) must be between 1 and multi_pulls ({})",
+                self.multi_cost,
+                self.multi_pulls
+            );
+        }
+        Ok(())
+    }
+
+    /// Tickets needed for `pulls` pulls, every full multi-pull gets the discount
+    /// and the rest costs one ticket each.
+    pub fn cost_of(&self, pulls: u32) -> u32 {
+        let multi_pulls = self.multi_pulls.max(1);
+        (pulls / multi_pulls) * self.multi_cost + pulls % multi_pulls
+    }
+
+    /// Tickets given back when `refunded` pulls were refunded from a purchase that cost `cost`,
+    /// you can never get back more than what you paid.
+    pub fn refund_of(&self, cost: u32, refunded: u32) -> u32 {
+        refunded.min(cost)
+    }
+}
+
+/// A change of ticket balance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transaction {
+    /// Tickets paid for a number of pulls.
+    Pull { pulls: u32, cost: u32 },
+    /// Tickets given back from refunded pulls.
+    Refund { tickets: u32 },
+    /// Tickets bought from the shop.
+    Purchase { tickets: u32 },
+}
+
+impl Transaction {
+    /// Number of tickets moved by this transaction.
+    pub fn tickets(&self) -> u32 {
+        match self {
+            Transaction::Pull { cost, .. } => *cost,
+            Transaction::Refund { tickets } | Transaction::Purchase { tickets } => *tickets,
+        }
+    }
+}
+
+/// A pull cost more tickets than the wallet holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotEnoughTickets {
+    pub needed: u32,
+    pub available: u32,
+}
+
+impl fmt::Display for NotEnoughTickets {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "not enough tickets: {} needed, you have {}",
+            self.needed, self.available
+        )
+    }
+}
+
+impl std::error::Error for NotEnoughTickets {}
+
+/// Holds the tickets of a player, the balance can only be changed through [`Transaction`]s.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Wallet {
+    tickets: u32,
+}
+
+impl Wallet {
+    pub fn new(tickets: u32) -> Self {
+        Wallet { tickets }
+    }
+
+    pub fn tickets(&self) -> u32 {
+        self.tickets
+    }
+
+    /// Apply a transaction, the balance stays the same if it fails.
+    pub fn apply(&mut self, tx: Transaction) -> Result<()> {
+        self.tickets = match tx {
+            Transaction::Pull { cost, .. } => {
+                self.tickets.checked_sub(cost).ok_or(NotEnoughTickets {
+                    needed: cost,
+                    available: self.tickets,
+                })?
+            }
+            Transaction::Refund { tickets } | Transaction::Purchase { tickets } => self
+                .tickets
+                .checked_add(tickets)
+                .ok_or_else(|| anyhow!("that's way too many tickets"))?,
+        };
+        Ok(())
+    }
+
+    /// Pay for `pulls` pulls, and return the transaction made.
+    pub fn pay_for_pulls(&mut self, pricing: &Pricing, pulls: u32) -> Result<Transaction> {
+        let tx = Transaction::Pull {
+            pulls,
+            cost: pricing.cost_of(pulls),
+        };
+        self.apply(tx)?;
+        Ok(tx)
+    }
+
+    /// Give back tickets of `refunded` pulls that were paid with `paid`,
+    /// and return the transaction made.
+    pub fn refund(
+        &mut self,
+        pricing: &Pricing,
+        paid: Transaction,
+        refunded: u32,
+    ) -> Result<Transaction> {
+        let cost = match paid {
+            Transaction::Pull { cost, .. } => cost,
+            _ => bail!("only pulls can be refunded"),
+        };
+        let tx = Transaction::Refund {
+            tickets: pricing.refund_of(cost, refunded),
+        };
+        self.apply(tx)?;
+        Ok(tx)
+    }
+}
+
+#[cfg(test)]
+mod wallet_tests {
+    use super::{NotEnoughTickets, Pricing, Transaction, Wallet};
+
+    #[test]
+    fn multi_pull_discount() {
+        let pricing = Pricing::default();
+        assert_eq!(pricing.cost_of(0), 0);
+        assert_eq!(pricing.cost_of(1), 1);
+        assert_eq!(pricing.cost_of(9), 9);
+        assert_eq!(pricing.cost_of(10), 9);
+        assert_eq!(pricing.cost_of(12), 11);
+        assert_eq!(pricing.cost_of(20), 18);
+    }
+
+    #[test]
+    fn pay_and_refund() {
+        let pricing = Pricing::default();
+        let mut wallet = Wallet::new(9);
+
+        let paid = wallet.pay_for_pulls(&pricing, 10).unwrap();
+        assert_eq!(paid, Transaction::Pull { pulls: 10, cost: 9 });
+        assert_eq!(wallet.tickets(), 0);
+
+        // refunds never exceed what was paid
+        let refund = wallet.refund(&pricing, paid, 10).unwrap();
+        assert_eq!(refund, Transaction::Refund { tickets: 9 });
+        assert_eq!(wallet.tickets(), 9);
+    }
+
+    #[test]
+    fn single_pull_refund() {
+        let pricing = Pricing::default();
+        let mut wallet = Wallet::new(1);
+
+        let paid = wallet.pay_for_pulls(&pricing, 1).unwrap();
+        wallet.refund(&pricing, paid, 1).unwrap();
+        assert_eq!(wallet.tickets(), 1);
+
+        let paid = wallet.pay_for_pulls(&pricing, 1).unwrap();
+        wallet.refund(&pricing, paid, 0).unwrap();
+        assert_eq!(wallet.tickets(), 0);
+    }
+
+    #[test]
+    fn not_enough_tickets() {
+        let pricing = Pricing::default();
+        let mut wallet = Wallet::new(8);
+        let err = wallet.pay_for_pulls(&pricing, 10).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<NotEnoughTickets>(),
+            Some(&NotEnoughTickets {
+                needed: 9,
+                available: 8
+            })
+        );
+        assert_eq!(wallet.tickets(), 8);
+
+        let mut wallet = Wallet::new(u32::MAX);
+        assert!(wallet.apply(Transaction::Purchase { tickets: 1 }).is_err());
+        assert_eq!(wallet.tickets(), u32::MAX);
+    }
+
+    #[test]
+    fn refund_only_pulls() {
+        let pricing = Pricing::default();
+        let mut wallet = Wallet::new(0);
+        let bought = Transaction::Purchase { tickets: 5 };
+        wallet.apply(bought).unwrap();
+        assert!(wallet.refund(&pricing, bought, 1).is_err());
+        assert_eq!(wallet.tickets(), 5);
+    }
+
+    #[test]
+    fn reject_bad_pricing() {
+        let pricing = Pricing {
+            multi_pulls: 10,
+            multi_cost: 11,
+        };
+        assert!(pricing.validate().is_err());
+        assert!(Pricing::default().validate().is_ok());
+    }
+}
@@ -0,0 +1,698 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{AngleBracketedGenericArguments, DataStruct, DeriveInput, Token, TypePath};
+
+/// Option type unwrapper for Builder macro.
+///
+/// Unwrap option type if the field is already known,
+/// if the given type is not an Option<> type, do nothing.
+///
+/// # Example
+///
+/// ---------------------------------------
+/// ```rust,ignore
+/// let (success, unwrapped) = unwrap_option(ty);
+/// if success {
+///     println!("type unwrapped to: {:?}", unwrapped);
+/// } else {
+///     println!("origin type is not Option<> wrapped, return the original reference");
+/// }
+/// ```
+fn unwrap_option(ty: &syn::Type) -> (bool, &syn::Type) {
+    if let syn::Type::Path(TypePath { ref path, .. }) = ty {
+        if !path.segments.is_empty() && path.segments[0].ident == "Option" {
+            if let syn::PathArguments::AngleBracketed(AngleBracketedGenericArguments {
+                args, ..
+            }) = &path.segments[0].arguments
+            {
+                if let (1, syn::GenericArgument::Type(ref new_ty)) = (args.len(), &args[0]) {
+                    return (true, new_ty);
+                }
+            }
+        }
+    }
+
+    (false, ty)
+}
+
+/// A single option inside of `#[builder(...)]`.
+enum BuilderOpt {
+    /// `default` or `default = <EXPR>`
+    Default(syn::Ident, Option<syn::Expr>),
+    /// `into`
+    Into(syn::Ident),
+    /// `validate = <PATH>`
+    Validate(syn::Ident, syn::Path),
+    /// `skip`
+    Skip(syn::Ident),
+}
+
+impl Parse for BuilderOpt {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name: syn::Ident = input.parse()?;
+        match name.to_string().as_str() {
+            "default" => {
+                let expr = if input.peek(Token![=]) {
+                    input.parse::<Token![=]>()?;
+                    Some(input.parse()?)
+                } else {
+                    None
+                };
+                Ok(BuilderOpt::Default(name, expr))
+            }
+            "into" => Ok(BuilderOpt::Into(name)),
+            "validate" => {
+                input.parse::<Token![=]>()?;
+                let path = input.parse()?;
+                Ok(BuilderOpt::Validate(name, path))
+            }
+            "skip" => Ok(BuilderOpt::Skip(name)),
+            _ => Err(syn::Error::new(
+                name.span(),
+                format!(
+                    "unknown builder option `{}`, expected one of `default`, `into`, `validate`, `skip`",
+                    name
+                ),
+            )),
+        }
+    }
+}
+
+impl BuilderOpt {
+    fn ident(&self) -> &syn::Ident {
+        match self {
+            BuilderOpt::Default(ident, _)
+            | BuilderOpt::Into(ident)
+            | BuilderOpt::Validate(ident, _)
+            | BuilderOpt::Skip(ident) => ident,
+        }
+    }
+}
+
+/// Options of a field, collected from all of its `#[builder(...)]` attributes.
+#[derive(Default)]
+struct FieldOpts {
+    /// `Some(None)` means the default value comes from `Default::default()`.
+    default: Option<Option<syn::Expr>>,
+    into: bool,
+    validate: Option<syn::Path>,
+    skip: bool,
+}
+
+impl FieldOpts {
+    fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut opts = FieldOpts::default();
+        let mut seen: Vec<syn::Ident> = vec![];
+        for attr in attrs.iter().filter(|a| a.path.is_ident("builder")) {
+            let parsed =
+                attr.parse_args_with(Punctuated::<BuilderOpt, Token![,]>::parse_terminated)?;
+            for opt in parsed {
+                let ident = opt.ident().clone();
+                if seen.contains(&ident) {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        format!("duplicated builder option `{}`", ident),
+                    ));
+                }
+                match opt {
+                    BuilderOpt::Default(_, expr) => opts.default = Some(expr),
+                    BuilderOpt::Into(_) => opts.into = true,
+                    BuilderOpt::Validate(_, path) => opts.validate = Some(path),
+                    BuilderOpt::Skip(_) => opts.skip = true,
+                }
+                seen.push(ident);
+            }
+        }
+
+        // a skipped field has no setter, so these options would never take effect
+        if opts.skip {
+            if let Some(ident) = seen.iter().find(|i| *i == "into" || *i == "validate") {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!("`{}` cannot be used on a skipped field", ident),
+                ));
+            }
+        }
+        Ok(opts)
+    }
+}
+
+/// Options of the whole struct, from `#[builder(...)]` on the struct itself.
+#[derive(Default)]
+struct StructOpts {
+    /// Report every missing field at once, instead of only the first one.
+    all_missing: bool,
+    /// Check required fields at compile time, see `Builder::derive_typestate`.
+    typestate: bool,
+}
+
+impl StructOpts {
+    fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut opts = StructOpts::default();
+        let mut seen: Vec<syn::Ident> = vec![];
+        for attr in attrs.iter().filter(|a| a.path.is_ident("builder")) {
+            let parsed =
+                attr.parse_args_with(Punctuated::<syn::Ident, Token![,]>::parse_terminated)?;
+            for ident in parsed {
+                let flag = match ident.to_string().as_str() {
+                    "all_missing" => &mut opts.all_missing,
+                    "typestate" => &mut opts.typestate,
+                    _ => {
+                        return Err(syn::Error::new(
+                            ident.span(),
+                            format!(
+                                "unknown builder option `{}` of a struct, expected `all_missing` or `typestate`",
+                                ident
+                            ),
+                        ))
+                    }
+                };
+                if *flag {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        format!("duplicated builder option `{}`", ident),
+                    ));
+                }
+                *flag = true;
+                seen.push(ident);
+            }
+        }
+
+        // nothing could be missing when the compiler already checked it
+        if opts.all_missing && opts.typestate {
+            let ident = seen.iter().find(|i| *i == "all_missing").unwrap();
+            return Err(syn::Error::new(
+                ident.span(),
+                "`all_missing` cannot be used along with `typestate`",
+            ));
+        }
+        Ok(opts)
+    }
+}
+
+/// A field of the struct, tuple struct fields are named by their position, e.g. `_0`.
+struct BuilderField<'a> {
+    name: syn::Ident,
+    ty: &'a syn::Type,
+    opts: FieldOpts,
+}
+
+impl BuilderField<'_> {
+    /// Whether the field has to be set before building.
+    fn is_required(&self) -> bool {
+        !self.opts.skip && self.opts.default.is_none() && !unwrap_option(self.ty).0
+    }
+
+    /// Type of the field in the builder, which is always an `Option`.
+    fn builder_field(&self) -> TokenStream {
+        let name = &self.name;
+        let ty = self.ty;
+        let (is_option, _) = unwrap_option(ty);
+
+        if is_option {
+            quote! {#name: #ty}
+        } else {
+            quote! {#name: std::option::Option<#ty>}
+        }
+    }
+
+    /// Value of the field when building, bound to a variable named after the field.
+    ///
+    /// `take` is how the value is taken out of the builder, `missing` is evaluated when
+    /// a required field was never set.
+    fn build_value(
+        &self,
+        take: &TokenStream,
+        missing: TokenStream,
+        error_ident: &syn::Ident,
+    ) -> TokenStream {
+        let name = &self.name;
+        let (is_option, _) = unwrap_option(self.ty);
+        let default = match &self.opts.default {
+            Some(Some(expr)) => Some(quote! { #expr }),
+            Some(None) => Some(quote! { std::default::Default::default() }),
+            None => None,
+        };
+
+        let value = match (self.opts.skip, is_option, default) {
+            (true, _, default) => {
+                default.unwrap_or_else(|| quote! { std::default::Default::default() })
+            }
+            (false, true, Some(default)) => quote! { self.#name #take.or_else(|| #default) },
+            (false, true, None) => quote! { self.#name #take },
+            (false, false, Some(default)) => {
+                quote! { self.#name #take.unwrap_or_else(|| #default) }
+            }
+            (false, false, None) => quote! {
+                match self.#name #take {
+                    std::option::Option::Some(value) => value,
+                    std::option::Option::None => #missing,
+                }
+            },
+        };
+        let validate = self.opts.validate.as_ref().map(|path| {
+            quote! {
+                if let std::result::Result::Err(e) = #path(&#name) {
+                    return std::result::Result::Err(#error_ident::ValidationFailed {
+                        field: stringify!(#name),
+                        message: e.to_string(),
+                    });
+                }
+            }
+        });
+
+        quote! {
+            let #name = #value;
+            #validate
+        }
+    }
+
+    fn setter_args(&self) -> (TokenStream, TokenStream, TokenStream) {
+        let name = &self.name;
+        let (_, ty) = unwrap_option(self.ty);
+
+        if self.opts.into {
+            (
+                quote! { <V: std::convert::Into<#ty>> },
+                quote! { #name: V },
+                quote! { std::option::Option::Some(#name.into()) },
+            )
+        } else {
+            (
+                quote! {},
+                quote! { #name: #ty },
+                quote! { std::option::Option::Some(#name) },
+            )
+        }
+    }
+}
+
+/// Implementation of `#[derive(Builder)]`.
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    let DeriveInput {
+        ident,
+        data,
+        attrs,
+        generics,
+        ..
+    } = input;
+    let struct_opts = StructOpts::from_attrs(&attrs)?;
+
+    let (struct_fields, is_tuple) = match data {
+        syn::Data::Struct(DataStruct {
+            fields: syn::Fields::Named(syn::FieldsNamed { named, .. }),
+            ..
+        }) => (named, false),
+        syn::Data::Struct(DataStruct {
+            fields: syn::Fields::Unnamed(syn::FieldsUnnamed { unnamed, .. }),
+            ..
+        }) => (unnamed, true),
+        _ => {
+            return Err(syn::Error::new(
+                ident.span(),
+                "Builder can only be derived for structs with fields",
+            ))
+        }
+    };
+
+    let mut fields = vec![];
+    let mut errors: Option<syn::Error> = None;
+    for (i, field) in struct_fields.iter().enumerate() {
+        match FieldOpts::from_attrs(&field.attrs) {
+            Ok(opts) => fields.push(BuilderField {
+                name: field
+                    .ident
+                    .clone()
+                    .unwrap_or_else(|| format_ident!("_{}", i, span = field.ty.span())),
+                ty: &field.ty,
+                opts,
+            }),
+            Err(e) => match &mut errors {
+                Some(errors) => errors.combine(e),
+                None => errors = Some(e),
+            },
+        }
+    }
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    let field_names = fields.iter().map(|f| &f.name);
+    let construct = if is_tuple {
+        quote! { #ident(#(#field_names,)*) }
+    } else {
+        quote! { #ident { #(#field_names,)* } }
+    };
+    let builder = Builder {
+        error_ident: format_ident!("{}BuilderError", ident),
+        builder_ident: format_ident!("{}Builder", ident),
+        ident,
+        generics,
+        fields,
+        construct,
+    };
+
+    let error_enum = builder.error_enum();
+    let builder = if struct_opts.typestate {
+        builder.derive_typestate()
+    } else {
+        builder.derive_runtime(struct_opts.all_missing)
+    };
+    Ok(quote! {
+        #error_enum
+        #builder
+    })
+}
+
+/// Everything needed to generate a builder.
+struct Builder<'a> {
+    ident: syn::Ident,
+    builder_ident: syn::Ident,
+    error_ident: syn::Ident,
+    generics: syn::Generics,
+    fields: Vec<BuilderField<'a>>,
+    /// Expression that creates the struct from variables named after its fields.
+    construct: TokenStream,
+}
+
+impl Builder<'_> {
+    fn error_enum(&self) -> TokenStream {
+        let error_ident = &self.error_ident;
+        let error_doc = format!("Error of building a `{}`.", self.ident);
+
+        quote! {
+            #[doc = #error_doc]
+            #[derive(Debug, Clone, PartialEq, Eq)]
+            pub enum #error_ident {
+                /// A required field was never set.
+                MissingField(&'static str),
+                /// Every required field that was never set, only with `#[builder(all_missing)]`.
+                MissingFields(std::vec::Vec<&'static str>),
+                /// A field was rejected by its validator.
+                ValidationFailed {
+                    field: &'static str,
+                    message: std::string::String,
+                },
+            }
+
+            impl std::fmt::Display for #error_ident {
+                fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    match self {
+                        #error_ident::MissingField(field) => {
+                            write!(f, "missing required field `{}`", field)
+                        }
+                        #error_ident::MissingFields(fields) => {
+                            write!(f, "missing required fields: ")?;
+                            for (i, field) in fields.iter().enumerate() {
+                                if i > 0 {
+                                    write!(f, ", ")?;
+                                }
+                                write!(f, "`{}`", field)?;
+                            }
+                            std::result::Result::Ok(())
+                        }
+                        #error_ident::ValidationFailed { field, message } => {
+                            write!(f, "invalid value of field `{}`: {}", field, message)
+                        }
+                    }
+                }
+            }
+
+            impl std::error::Error for #error_ident {}
+        }
+    }
+
+    /// The default builder, which checks required fields when building.
+    fn derive_runtime(&self, all_missing: bool) -> TokenStream {
+        let Builder {
+            ident,
+            builder_ident,
+            error_ident,
+            generics,
+            fields,
+            construct,
+        } = self;
+
+        let builder_fields = fields
+            .iter()
+            .filter(|f| !f.opts.skip)
+            .map(|f| f.builder_field());
+        let setters = fields.iter().filter(|f| !f.opts.skip).map(|f| {
+            let name = &f.name;
+            let (params, arg, value) = f.setter_args();
+
+            quote! {
+                pub fn #name #params(&mut self, #arg) -> &mut Self {
+                    self.#name = #value;
+                    self
+                }
+            }
+        });
+        let take = quote! { .clone() };
+        let build_values = fields.iter().map(|f| {
+            let name = &f.name;
+            f.build_value(
+                &take,
+                quote! {
+                    return std::result::Result::Err(#error_ident::MissingField(stringify!(#name)))
+                },
+                error_ident,
+            )
+        });
+        let check_missing = if all_missing {
+            let required: Vec<_> = fields
+                .iter()
+                .filter(|f| f.is_required())
+                .map(|f| &f.name)
+                .collect();
+            let count = required.len();
+            Some(quote! {
+                let required: [(bool, &'static str); #count] =
+                    [#((self.#required.is_none(), stringify!(#required)),)*];
+                let missing: std::vec::Vec<&'static str> = required
+                    .into_iter()
+                    .filter_map(|(missing, name)| missing.then_some(name))
+                    .collect();
+                if !missing.is_empty() {
+                    return std::result::Result::Err(#error_ident::MissingFields(missing));
+                }
+            })
+        } else {
+            None
+        };
+        let empty_fields = fields.iter().filter(|f| !f.opts.skip).map(|f| {
+            let name = &f.name;
+
+            quote! { #name: None }
+        });
+
+        // values are cloned out of the builder, so every type parameter has to be cloneable
+        let clone_bounds: Vec<_> = generics
+            .type_params()
+            .map(|p| {
+                let param = &p.ident;
+                quote! { #param: std::clone::Clone }
+            })
+            .collect();
+        let build_where = if clone_bounds.is_empty() {
+            None
+        } else {
+            Some(quote! { where #(#clone_bounds,)* })
+        };
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+        quote! {
+            pub struct #builder_ident #generics #where_clause {
+                #(#builder_fields,)*
+                /// Keeps the generic parameters used even if every field using them was skipped.
+                __marker: std::marker::PhantomData<fn() -> #ident #ty_generics>,
+            }
+
+            impl #impl_generics #builder_ident #ty_generics #where_clause {
+                #(#setters)*
+
+                pub fn build(&self) -> std::result::Result<#ident #ty_generics, #error_ident>
+                #build_where
+                {
+                    #check_missing
+                    #(#build_values)*
+                    std::result::Result::Ok(#construct)
+                }
+            }
+
+            impl #impl_generics #ident #ty_generics #where_clause {
+                pub fn builder() -> #builder_ident #ty_generics {
+                    #builder_ident {
+                        #(#empty_fields,)*
+                        __marker: std::marker::PhantomData,
+                    }
+                }
+            }
+        }
+    }
+
+    /// The `#[builder(typestate)]` builder, which has a marker type parameter for each
+    /// required field, either `<Builder>Set` or `<Builder>Unset`. Setting a required field
+    /// changes its marker to `Set`, and `build()` only exists when every marker is `Set`.
+    fn derive_typestate(&self) -> TokenStream {
+        let Builder {
+            ident,
+            builder_ident,
+            error_ident,
+            generics,
+            fields,
+            construct,
+        } = self;
+        let set_ident = format_ident!("{}Set", builder_ident);
+        let unset_ident = format_ident!("{}Unset", builder_ident);
+        let set_doc = format!("A required field of `{}` that was set.", builder_ident);
+        let unset_doc = format!(
+            "A required field of `{}` that was not set yet.",
+            builder_ident
+        );
+
+        let required: Vec<&syn::Ident> = fields
+            .iter()
+            .filter(|f| f.is_required())
+            .map(|f| &f.name)
+            .collect();
+        let states: Vec<syn::Ident> = (0..required.len())
+            .map(|i| format_ident!("__S{}", i))
+            .collect();
+        // generic arguments of the struct itself, e.g. `'a, T, N`
+        let args: Vec<TokenStream> = generics
+            .params
+            .iter()
+            .map(|p| match p {
+                syn::GenericParam::Type(t) => {
+                    let ident = &t.ident;
+                    quote! { #ident }
+                }
+                syn::GenericParam::Lifetime(l) => {
+                    let lifetime = &l.lifetime;
+                    quote! { #lifetime }
+                }
+                syn::GenericParam::Const(c) => {
+                    let ident = &c.ident;
+                    quote! { #ident }
+                }
+            })
+            .collect();
+        // markers come after the struct's own parameters, which can't have defaults then
+        let mut state_generics = generics.clone();
+        for param in state_generics.params.iter_mut() {
+            match param {
+                syn::GenericParam::Type(t) => {
+                    t.eq_token = None;
+                    t.default = None;
+                }
+                syn::GenericParam::Const(c) => {
+                    c.eq_token = None;
+                    c.default = None;
+                }
+                syn::GenericParam::Lifetime(_) => {}
+            }
+        }
+        state_generics.params.extend(
+            states
+                .iter()
+                .map(|s| -> syn::GenericParam { syn::parse_quote!(#s) }),
+        );
+        let (state_impl_generics, state_ty_generics, _) = state_generics.split_for_impl();
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+        let builder_fields = fields
+            .iter()
+            .filter(|f| !f.opts.skip)
+            .map(|f| f.builder_field());
+        let setters = fields.iter().filter(|f| !f.opts.skip).map(|f| {
+            let name = &f.name;
+            let (params, arg, value) = f.setter_args();
+
+            match required.iter().position(|r| *r == name) {
+                Some(pos) => {
+                    let next_states = states.iter().enumerate().map(|(i, s)| {
+                        if i == pos {
+                            quote! { #set_ident }
+                        } else {
+                            quote! { #s }
+                        }
+                    });
+                    let others = fields
+                        .iter()
+                        .filter(|o| !o.opts.skip && o.name != *name)
+                        .map(|o| &o.name);
+                    quote! {
+                        pub fn #name #params(self, #arg)
+                            -> #builder_ident<#(#args,)* #(#next_states,)*>
+                        {
+                            #builder_ident {
+                                #name: #value,
+                                #(#others: self.#others,)*
+                                __marker: std::marker::PhantomData,
+                            }
+                        }
+                    }
+                }
+                None => quote! {
+                    pub fn #name #params(mut self, #arg) -> Self {
+                        self.#name = #value;
+                        self
+                    }
+                },
+            }
+        });
+        let take = quote! {};
+        let build_values = fields.iter().map(|f| {
+            f.build_value(
+                &take,
+                quote! { unreachable!("required fields are all set according to the typestate") },
+                error_ident,
+            )
+        });
+        let empty_fields = fields.iter().filter(|f| !f.opts.skip).map(|f| {
+            let name = &f.name;
+
+            quote! { #name: None }
+        });
+        let all_set = states.iter().map(|_| &set_ident);
+        let all_unset = states.iter().map(|_| &unset_ident);
+
+        quote! {
+            #[doc = #set_doc]
+            pub struct #set_ident;
+
+            #[doc = #unset_doc]
+            pub struct #unset_ident;
+
+            pub struct #builder_ident #state_generics #where_clause {
+                #(#builder_fields,)*
+                /// Keeps the generic parameters used, including the markers of required fields.
+                __marker: std::marker::PhantomData<fn() -> (#ident #ty_generics, #(#states,)*)>,
+            }
+
+            impl #state_impl_generics #builder_ident #state_ty_generics #where_clause {
+                #(#setters)*
+            }
+
+            impl #impl_generics #builder_ident<#(#args,)* #(#all_set,)*> #where_clause {
+                pub fn build(self) -> std::result::Result<#ident #ty_generics, #error_ident> {
+                    #(#build_values)*
+                    std::result::Result::Ok(#construct)
+                }
+            }
+
+            impl #impl_generics #ident #ty_generics #where_clause {
+                pub fn builder() -> #builder_ident<#(#args,)* #(#all_unset,)*> {
+                    #builder_ident {
+                        #(#empty_fields,)*
+                        __marker: std::marker::PhantomData,
+                    }
+                }
+            }
+        }
+    }
+}
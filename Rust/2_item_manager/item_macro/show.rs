@@ -0,0 +1,353 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::DeriveInput;
+
+/// Options of a field or an enum variant, from `#[color = "..."]` and `#[show(...)]`.
+#[derive(Default)]
+struct ShowOpts {
+    /// Escape code that starts the color.
+    color: Option<String>,
+    skip: bool,
+    rename: Option<String>,
+}
+
+impl ShowOpts {
+    fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut opts = ShowOpts::default();
+        for attr in attrs {
+            if attr.path.is_ident("color") {
+                match attr.parse_meta()? {
+                    syn::Meta::NameValue(syn::MetaNameValue {
+                        lit: syn::Lit::Str(color),
+                        ..
+                    }) => opts.color = Some(color_platte(&color)?),
+                    meta => {
+                        return Err(syn::Error::new(
+                            meta.span(),
+                            "expected a color name, e.g. `#[color = \"red\"]`",
+                        ))
+                    }
+                }
+            } else if attr.path.is_ident("show") {
+                let nested = match attr.parse_meta()? {
+                    syn::Meta::List(list) => list.nested,
+                    meta => {
+                        return Err(syn::Error::new(
+                            meta.span(),
+                            "expected `#[show(skip)]` or `#[show(rename = \"...\")]`",
+                        ))
+                    }
+                };
+                for meta in nested {
+                    match meta {
+                        syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("skip") => {
+                            opts.skip = true
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                            path,
+                            lit: syn::Lit::Str(name),
+                            ..
+                        })) if path.is_ident("rename") => opts.rename = Some(name.value()),
+                        meta => {
+                            return Err(syn::Error::new(
+                                meta.span(),
+                                "unknown show option, expected `skip` or `rename = \"...\"`",
+                            ))
+                        }
+                    }
+                }
+            }
+        }
+        Ok(opts)
+    }
+
+    /// Code that writes `value` into the formatter `f` with its color, `debug` decides
+    /// whether the value is written with `{:?}` or `{}`.
+    fn write_colored(&self, value: TokenStream, debug: bool) -> TokenStream {
+        let fmt = match (&self.color, debug) {
+            (Some(color), true) => format!("{}{{:?}}\x1b[0m", color.replace('{', "{{")),
+            (Some(color), false) => format!("{}{{}}\x1b[0m", color.replace('{', "{{")),
+            (None, true) => "{:?}".to_string(),
+            (None, false) => "{}".to_string(),
+        };
+        quote! { write!(f, #fmt, #value)?; }
+    }
+}
+
+/// Implementation of `#[derive(Show)]`.
+///
+/// Enums are shown as the name of their variants, and structs as a list of `field: value`
+/// with every value in its `Debug` format, separated by commas or, with `{:#}`, new lines.
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    let DeriveInput {
+        ident,
+        data,
+        generics,
+        ..
+    } = input;
+
+    let is_struct = matches!(data, syn::Data::Struct(_));
+    let body = match data {
+        syn::Data::Enum(syn::DataEnum { variants, .. }) => {
+            let arms = variants
+                .iter()
+                .map(|v| {
+                    let opts = ShowOpts::from_attrs(&v.attrs)?;
+                    let name = &v.ident;
+                    let pattern = match v.fields {
+                        syn::Fields::Named(_) => quote! { Self::#name { .. } },
+                        syn::Fields::Unnamed(_) => quote! { Self::#name(..) },
+                        syn::Fields::Unit => quote! { Self::#name },
+                    };
+                    let write = if opts.skip {
+                        None
+                    } else {
+                        let title = opts.rename.clone().unwrap_or_else(|| name.to_string());
+                        Some(opts.write_colored(quote! { #title }, false))
+                    };
+                    Ok(quote! { #pattern => { #write } })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+
+            if arms.is_empty() {
+                quote! { match *self {} }
+            } else {
+                quote! {
+                    match self {
+                        #(#arms)*
+                    }
+                    std::result::Result::Ok(())
+                }
+            }
+        }
+        syn::Data::Struct(syn::DataStruct { fields, .. }) => {
+            let mut shown = vec![];
+            for (i, field) in fields.iter().enumerate() {
+                let opts = ShowOpts::from_attrs(&field.attrs)?;
+                if opts.skip {
+                    continue;
+                }
+                let (member, title) = match &field.ident {
+                    Some(name) => (quote! { #name }, name.to_string()),
+                    None => {
+                        let index = syn::Index::from(i);
+                        (quote! { #index }, i.to_string())
+                    }
+                };
+                let title = opts.rename.clone().unwrap_or(title);
+                let value = opts.write_colored(quote! { &self.#member }, true);
+                let sep = if shown.is_empty() {
+                    None
+                } else {
+                    Some(quote! { f.write_str(sep)?; })
+                };
+                shown.push(quote! {
+                    #sep
+                    write!(f, "{}: ", #title)?;
+                    #value
+                });
+            }
+
+            let sep = if shown.len() > 1 {
+                Some(quote! { let sep = if f.alternate() { "\n" } else { ", " }; })
+            } else {
+                None
+            };
+            if let syn::Fields::Unit = fields {
+                let name = ident.to_string();
+                quote! { f.write_str(#name) }
+            } else {
+                quote! {
+                    #sep
+                    #(#shown)*
+                    std::result::Result::Ok(())
+                }
+            }
+        }
+        syn::Data::Union(_) => {
+            return Err(syn::Error::new(
+                ident.span(),
+                "Show cannot be derived for unions",
+            ))
+        }
+    };
+
+    // every field of a struct is written with its `Debug` format
+    let mut generics = generics;
+    if is_struct {
+        let params: Vec<syn::Ident> = generics.type_params().map(|p| p.ident.clone()).collect();
+        let where_clause = generics.make_where_clause();
+        for param in params {
+            where_clause
+                .predicates
+                .push(syn::parse_quote!(#param: std::fmt::Debug));
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics std::fmt::Display for #ident #ty_generics #where_clause {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                #body
+            }
+        }
+    })
+}
+
+/// Names of the 8 base ANSI colors, in the order of their codes; `bright_<name>` gives the other 8.
+const COLOR_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// Colors that were the only ones supported before styles, given alone they stay bold
+/// so the code using them looks the same.
+const LEGACY_COLORS: [&str; 6] = ["red", "yellow", "purple", "blue", "cyan", "white"];
+
+/// Function to get the escape code that starts a style, from the value of a `#[color = "..."]`.
+///
+/// A style is a list of words separated by spaces, which has at most one color and any of
+/// the `bold`, `italic` and `underline` modifiers. A color could be one of the 16 ANSI color names
+/// (`red`, `bright_red`...), an index of the 256-color palette or `#RRGGBB` truecolor.
+/// One of the [`LEGACY_COLORS`] alone is bold.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let style = color_platte(&syn::parse_quote!("underline red"))?;
+/// assert_eq!(style, "\x1b[31;4m");
+/// ```
+fn color_platte(lit: &syn::LitStr) -> syn::Result<String> {
+    let value = lit.value();
+    if LEGACY_COLORS.contains(&value.as_str()) {
+        let code = parse_color(&value).expect("legacy colors are ANSI colors");
+        return Ok(format!("\x1b[{};1m", code));
+    }
+    let mut color: Option<String> = None;
+    let mut modifiers = [
+        ("bold", "1", false),
+        ("italic", "3", false),
+        ("underline", "4", false),
+    ];
+
+    for word in value.split_whitespace() {
+        if let Some((_, _, used)) = modifiers.iter_mut().find(|(name, ..)| *name == word) {
+            *used = true;
+            continue;
+        }
+        let code = parse_color(word).ok_or_else(|| {
+            syn::Error::new(
+                lit.span(),
+                format!(
+                    "unknown color `{}`, expected an ANSI color name (e.g. `red`, `bright_red`), \
+                    a 256-color index, `#RRGGBB`, or one of `bold`, `italic`, `underline`",
+                    word
+                ),
+            )
+        })?;
+        if color.replace(code).is_some() {
+            return Err(syn::Error::new(
+                lit.span(),
+                format!("more than one color in `{}`", value),
+            ));
+        }
+    }
+
+    let codes: Vec<String> = color
+        .into_iter()
+        .chain(
+            modifiers
+                .iter()
+                .filter(|(.., used)| *used)
+                .map(|(_, code, _)| code.to_string()),
+        )
+        .collect();
+    if codes.is_empty() {
+        return Err(syn::Error::new(lit.span(), "expected a color or a style"));
+    }
+    Ok(format!("\x1b[{}m", codes.join(";")))
+}
+
+/// SGR parameters of a single color.
+fn parse_color(word: &str) -> Option<String> {
+    let name = word.to_ascii_lowercase();
+    // a couple of aliases people tend to use
+    let name = match name.as_str() {
+        "purple" => "magenta",
+        "gray" | "grey" => "bright_black",
+        name => name,
+    };
+
+    if let Some(idx) = COLOR_NAMES.iter().position(|c| *c == name) {
+        return Some((30 + idx).to_string());
+    }
+    if let Some(idx) = name
+        .strip_prefix("bright_")
+        .and_then(|n| COLOR_NAMES.iter().position(|c| *c == n))
+    {
+        return Some((90 + idx).to_string());
+    }
+    if let Ok(idx) = name.parse::<u8>() {
+        return Some(format!("38;5;{}", idx));
+    }
+    let hex = name.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(format!(
+        "38;2;{};{};{}",
+        channel(0)?,
+        channel(2)?,
+        channel(4)?
+    ))
+}
+
+#[cfg(test)]
+mod show_tests {
+    use super::color_platte;
+
+    fn style(value: &str) -> syn::Result<String> {
+        color_platte(&syn::LitStr::new(value, proc_macro2::Span::call_site()))
+    }
+
+    #[test]
+    fn ansi_colors() {
+        assert_eq!(style("green").unwrap(), "\x1b[32m");
+        assert_eq!(style("Purple").unwrap(), "\x1b[35m");
+        assert_eq!(style("bright_white").unwrap(), "\x1b[97m");
+        assert_eq!(style("grey").unwrap(), "\x1b[90m");
+    }
+
+    #[test]
+    fn legacy_colors_are_bold() {
+        assert_eq!(style("red").unwrap(), "\x1b[31;1m");
+        assert_eq!(style("purple").unwrap(), "\x1b[35;1m");
+        assert_eq!(style("white").unwrap(), "\x1b[37;1m");
+        // only alone, a style with more words is what it says
+        assert_eq!(style("italic red").unwrap(), "\x1b[31;3m");
+    }
+
+    #[test]
+    fn extended_colors() {
+        assert_eq!(style("208").unwrap(), "\x1b[38;5;208m");
+        assert_eq!(style("#FF8000").unwrap(), "\x1b[38;2;255;128;0m");
+        assert!(style("256").is_err());
+        assert!(style("#FF80").is_err());
+        assert!(style("#GG8000").is_err());
+    }
+
+    #[test]
+    fn modifiers() {
+        assert_eq!(style("bold yellow").unwrap(), "\x1b[33;1m");
+        assert_eq!(style("underline italic").unwrap(), "\x1b[3;4m");
+        assert_eq!(style("bold #000000").unwrap(), "\x1b[38;2;0;0;0;1m");
+    }
+
+    #[test]
+    fn bad_styles() {
+        assert!(style("").is_err());
+        assert!(style("orange").is_err());
+        assert!(style("red blue").is_err());
+    }
+}
@@ -0,0 +1,134 @@
+use item_macro::Builder;
+use std::error::Error;
+
+fn positive(value: &u32) -> Result<(), String> {
+    if *value == 0 {
+        Err("must be positive".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Weapon {
+    #[builder(into)]
+    name: String,
+    #[builder(default = 10, validate = positive)]
+    damage: u32,
+    #[builder(default)]
+    durability: u16,
+    #[builder(skip)]
+    kills: u64,
+    #[builder(default = Some("Ordinary".to_string()))]
+    title: Option<String>,
+    price: Option<u32>,
+}
+
+#[test]
+fn required_fields() {
+    assert_eq!(
+        Weapon::builder().build(),
+        Err(WeaponBuilderError::MissingField("name"))
+    );
+
+    let weapon = Weapon::builder().name("Rusted Sword").build().unwrap();
+    assert_eq!(
+        weapon,
+        Weapon {
+            name: "Rusted Sword".to_string(),
+            damage: 10,
+            durability: 0,
+            kills: 0,
+            title: Some("Ordinary".to_string()),
+            price: None,
+        }
+    );
+}
+
+#[test]
+fn override_defaults() {
+    let weapon = Weapon::builder()
+        .name(String::from("Black Sword"))
+        .damage(99)
+        .durability(300)
+        .title("Cursed".to_string())
+        .price(4000)
+        .build()
+        .unwrap();
+    assert_eq!(weapon.damage, 99);
+    assert_eq!(weapon.durability, 300);
+    assert_eq!(weapon.title.as_deref(), Some("Cursed"));
+    assert_eq!(weapon.price, Some(4000));
+}
+
+#[test]
+fn validate_field() {
+    let err = Weapon::builder()
+        .name("Wooden Stick")
+        .damage(0)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        WeaponBuilderError::ValidationFailed {
+            field: "damage",
+            message: "must be positive".to_string()
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "invalid value of field `damage`: must be positive"
+    );
+}
+
+#[derive(Builder, Debug)]
+#[builder(all_missing)]
+pub struct Armour {
+    name: String,
+    defence: u32,
+    #[builder(default)]
+    weight: u32,
+    set: Option<String>,
+}
+
+#[test]
+fn report_all_missing() {
+    let err = Armour::builder().build().unwrap_err();
+    assert_eq!(
+        err,
+        ArmourBuilderError::MissingFields(vec!["name", "defence"])
+    );
+    assert_eq!(
+        err.to_string(),
+        "missing required fields: `name`, `defence`"
+    );
+
+    let err = Armour::builder().defence(5).build().unwrap_err();
+    assert_eq!(err, ArmourBuilderError::MissingFields(vec!["name"]));
+
+    let armour = Armour::builder()
+        .name("Cap".into())
+        .defence(5)
+        .build()
+        .unwrap();
+    assert_eq!(
+        (
+            armour.name.as_str(),
+            armour.defence,
+            armour.weight,
+            armour.set
+        ),
+        ("Cap", 5, 0, None)
+    );
+}
+
+#[test]
+fn boxed_error() {
+    fn build() -> Result<Weapon, Box<dyn Error>> {
+        Ok(Weapon::builder().build()?)
+    }
+    assert_eq!(
+        build().unwrap_err().to_string(),
+        "missing required field `name`"
+    );
+}
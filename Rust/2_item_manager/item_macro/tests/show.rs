@@ -0,0 +1,109 @@
+use item_macro::Show;
+
+#[derive(Show)]
+pub enum Rarity {
+    #[color = "white"]
+    Normal,
+    #[color = "yellow"]
+    #[show(rename = "LEGENDARY")]
+    Legendary,
+    Unknown(u8),
+    #[show(skip)]
+    Hidden {
+        reason: String,
+    },
+}
+
+#[derive(Show)]
+pub struct Item {
+    name: String,
+    #[show(rename = "price")]
+    value: Option<u32>,
+    #[color = "red"]
+    damage: u32,
+    #[show(skip)]
+    secret: bool,
+}
+
+#[derive(Show)]
+pub struct Pair<T>(T, #[show(rename = "second")] T);
+
+#[derive(Show)]
+pub struct Nothing;
+
+fn item() -> Item {
+    Item {
+        name: String::from("Rusted Sword"),
+        value: Some(300),
+        damage: 12,
+        secret: true,
+    }
+}
+
+#[test]
+fn show_variants() {
+    assert_eq!(Rarity::Normal.to_string(), "\x1b[37;1mNormal\x1b[0m");
+    assert_eq!(Rarity::Legendary.to_string(), "\x1b[33;1mLEGENDARY\x1b[0m");
+    assert_eq!(Rarity::Unknown(3).to_string(), "Unknown");
+    let hidden = Rarity::Hidden {
+        reason: String::from("spoiler"),
+    };
+    assert_eq!(hidden.to_string(), "");
+}
+
+#[test]
+fn show_fields() {
+    // skipped fields are still there, just not shown
+    assert!(item().secret);
+    assert_eq!(
+        format!("{}", item()),
+        "name: \"Rusted Sword\", price: Some(300), damage: \x1b[31;1m12\x1b[0m"
+    );
+    assert_eq!(
+        format!("{:#}", item()),
+        "name: \"Rusted Sword\"\nprice: Some(300)\ndamage: \x1b[31;1m12\x1b[0m"
+    );
+}
+
+#[test]
+fn show_tuple_and_unit() {
+    assert_eq!(Pair(1, 2).to_string(), "0: 1, second: 2");
+    assert_eq!(format!("{:#}", Pair("a", "b")), "0: \"a\"\nsecond: \"b\"");
+    assert_eq!(Nothing.to_string(), "Nothing");
+}
+
+#[derive(Debug, Show)]
+pub enum Element {
+    #[color = "bold bright_red"]
+    Fire,
+    #[color = "italic 39"]
+    Water,
+    #[color = "underline #80FF00"]
+    Grass,
+}
+
+#[derive(Show)]
+pub struct Rune {
+    #[color = "green"]
+    element: Element,
+    #[color = "bold italic white"]
+    level: u8,
+}
+
+#[test]
+fn show_styles() {
+    assert_eq!(Element::Fire.to_string(), "\x1b[91;1mFire\x1b[0m");
+    assert_eq!(Element::Water.to_string(), "\x1b[38;5;39;3mWater\x1b[0m");
+    assert_eq!(
+        Element::Grass.to_string(),
+        "\x1b[38;2;128;255;0;4mGrass\x1b[0m"
+    );
+    let rune = Rune {
+        element: Element::Grass,
+        level: 3,
+    };
+    assert_eq!(
+        rune.to_string(),
+        "element: \x1b[32mGrass\x1b[0m, level: \x1b[37;1;3m3\x1b[0m"
+    );
+}
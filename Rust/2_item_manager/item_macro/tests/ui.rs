@@ -0,0 +1,15 @@
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/generic.rs");
+    t.pass("tests/ui/lifetime.rs");
+    t.pass("tests/ui/where_clause.rs");
+    t.pass("tests/ui/tuple.rs");
+    t.pass("tests/ui/typestate.rs");
+    t.pass("tests/ui/empty_enum.rs");
+    t.compile_fail("tests/ui/enum.rs");
+    t.compile_fail("tests/ui/unknown_option.rs");
+    t.compile_fail("tests/ui/typestate_missing.rs");
+    t.compile_fail("tests/ui/unknown_color.rs");
+    t.compile_fail("tests/ui/variant_fields.rs");
+}
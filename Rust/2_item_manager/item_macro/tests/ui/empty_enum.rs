@@ -0,0 +1,13 @@
+use item_macro::Variants;
+
+#[derive(Variants, Debug, PartialEq, Eq)]
+pub enum Never {}
+
+fn main() {
+    assert!(Never::ALL.is_empty());
+    assert_eq!(Never::iter().count(), 0);
+    assert_eq!(
+        "anything".parse::<Never>(),
+        Err(NeverParseError(String::from("anything")))
+    );
+}
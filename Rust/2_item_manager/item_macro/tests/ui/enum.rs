@@ -0,0 +1,9 @@
+use item_macro::Builder;
+
+#[derive(Builder)]
+pub enum Shape {
+    Circle(u32),
+    Square(u32),
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+use item_macro::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Stack<T, const N: usize> {
+    item: T,
+    #[builder(default = N)]
+    max: usize,
+    #[builder(skip)]
+    history: Vec<T>,
+}
+
+fn main() {
+    let stack = Stack::<&str, 99>::builder().item("Potion").build().unwrap();
+    assert_eq!(
+        stack,
+        Stack {
+            item: "Potion",
+            max: 99,
+            history: vec![],
+        }
+    );
+}
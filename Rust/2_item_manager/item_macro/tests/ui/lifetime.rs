@@ -0,0 +1,19 @@
+use item_macro::Builder;
+
+#[derive(Builder, Debug)]
+pub struct Label<'a> {
+    #[builder(into)]
+    name: &'a str,
+    tags: Option<&'a [&'a str]>,
+}
+
+fn main() {
+    let name = String::from("Rusted Sword");
+    let label = Label::builder()
+        .name(name.as_str())
+        .tags(&["weapon", "sword"])
+        .build()
+        .unwrap();
+    assert_eq!(label.name, "Rusted Sword");
+    assert_eq!(label.tags.map(|t| t.len()), Some(2));
+}
@@ -0,0 +1,19 @@
+use item_macro::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(all_missing)]
+pub struct Coord(i32, i32, #[builder(default = 1)] u8, Option<String>);
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Wrapper<T>(#[builder(into)] T);
+
+fn main() {
+    let coord = Coord::builder()._0(3)._1(-7).build().unwrap();
+    assert_eq!(coord, Coord(3, -7, 1, None));
+
+    let err = Coord::builder()._1(0).build().unwrap_err();
+    assert_eq!(err, CoordBuilderError::MissingFields(vec!["_0"]));
+
+    let wrapper = Wrapper::<String>::builder()._0("Potion").build().unwrap();
+    assert_eq!(wrapper, Wrapper(String::from("Potion")));
+}
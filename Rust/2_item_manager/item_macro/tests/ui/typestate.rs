@@ -0,0 +1,41 @@
+use item_macro::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(typestate)]
+pub struct Item {
+    #[builder(into)]
+    name: String,
+    rarity: u8,
+    #[builder(default = 1)]
+    stack: u32,
+    value: Option<u32>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(typestate)]
+pub struct Pair<'a, T: Clone = u32>(&'a str, T);
+
+fn main() {
+    // required fields can be set in any order
+    let item = Item::builder()
+        .value(300)
+        .rarity(2)
+        .name("Rusted Sword")
+        .build()
+        .unwrap();
+    assert_eq!(
+        item,
+        Item {
+            name: "Rusted Sword".to_string(),
+            rarity: 2,
+            stack: 1,
+            value: Some(300),
+        }
+    );
+
+    let builder: ItemBuilder<ItemBuilderUnset, ItemBuilderSet> = Item::builder().rarity(0);
+    assert!(builder.name("Stick").build().is_ok());
+
+    let pair = Pair::builder()._1(5u32)._0("five").build().unwrap();
+    assert_eq!(pair, Pair("five", 5));
+}
@@ -0,0 +1,13 @@
+use item_macro::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Item {
+    name: String,
+    rarity: u8,
+    value: Option<u32>,
+}
+
+fn main() {
+    let _ = Item::builder().name(String::from("Rusted Sword")).value(300).build();
+}
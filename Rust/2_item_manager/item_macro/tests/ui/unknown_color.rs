@@ -0,0 +1,9 @@
+use item_macro::Show;
+
+#[derive(Show)]
+pub enum Rarity {
+    #[color = "bold orange"]
+    Rare,
+}
+
+fn main() {}
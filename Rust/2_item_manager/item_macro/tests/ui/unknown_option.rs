@@ -0,0 +1,9 @@
+use item_macro::Builder;
+
+#[derive(Builder)]
+pub struct Item<T> {
+    #[builder(defualt)]
+    name: T,
+}
+
+fn main() {}
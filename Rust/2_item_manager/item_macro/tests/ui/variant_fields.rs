@@ -0,0 +1,9 @@
+use item_macro::Variants;
+
+#[derive(Variants, PartialEq, Eq)]
+pub enum Slot {
+    Head,
+    Hand(u8),
+}
+
+fn main() {}
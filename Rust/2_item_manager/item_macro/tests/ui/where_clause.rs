@@ -0,0 +1,46 @@
+use item_macro::Builder;
+use std::fmt::Display;
+
+#[derive(Builder, Debug)]
+pub struct Tooltip<'a, T>
+where
+    T: Display + 'a,
+{
+    title: &'a str,
+    #[builder(validate = not_empty)]
+    lines: Vec<T>,
+}
+
+fn not_empty<T>(lines: &[T]) -> Result<(), &'static str> {
+    if lines.is_empty() {
+        Err("a tooltip needs at least one line")
+    } else {
+        Ok(())
+    }
+}
+
+impl<'a, T: Display + 'a> Tooltip<'a, T> {
+    fn render(&self) -> String {
+        let lines: Vec<String> = self.lines.iter().map(|l| l.to_string()).collect();
+        format!("{}: {}", self.title, lines.join(" / "))
+    }
+}
+
+fn main() {
+    let tooltip = Tooltip::builder()
+        .title("Damage")
+        .lines(vec![12, 30])
+        .build()
+        .unwrap();
+    assert_eq!(tooltip.render(), "Damage: 12 / 30");
+
+    let err = Tooltip::<u32>::builder()
+        .title("Damage")
+        .lines(vec![])
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid value of field `lines`: a tooltip needs at least one line"
+    );
+}
@@ -0,0 +1,41 @@
+use item_macro::Variants;
+
+#[derive(Variants, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rarity {
+    Normal,
+    Special,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+#[test]
+fn parse_and_print() {
+    assert_eq!("legendary".parse(), Ok(Rarity::Legendary));
+    assert_eq!(" RARE ".parse(), Ok(Rarity::Rare));
+    let err = "Mythic".parse::<Rarity>().unwrap_err();
+    assert_eq!(err, RarityParseError("Mythic".to_string()));
+    assert_eq!(err.to_string(), "'Mythic' is not a valid Rarity");
+
+    for rarity in Rarity::iter() {
+        assert_eq!(rarity.as_str().parse(), Ok(rarity));
+    }
+}
+
+#[test]
+fn declaration_order() {
+    assert_eq!(Rarity::ALL.len(), 5);
+    assert_eq!(Rarity::ALL[0], Rarity::Normal);
+    assert!(Rarity::Normal < Rarity::Special);
+    assert!(Rarity::Legendary > Rarity::Epic);
+    assert_eq!(Rarity::iter().max(), Some(Rarity::Legendary));
+    assert_eq!(Rarity::Rare.index(), 2);
+}
+
+#[test]
+fn upgrade_and_downgrade() {
+    assert_eq!(Rarity::Rare.next(), Some(Rarity::Epic));
+    assert_eq!(Rarity::Legendary.next(), None);
+    assert_eq!(Rarity::Special.prev(), Some(Rarity::Normal));
+    assert_eq!(Rarity::Normal.prev(), None);
+}
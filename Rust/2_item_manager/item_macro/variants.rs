@@ -0,0 +1,116 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::DeriveInput;
+
+/// Implementation of `#[derive(Variants)]`.
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    let DeriveInput {
+        ident,
+        data,
+        generics,
+        ..
+    } = input;
+
+    let variants = match data {
+        syn::Data::Enum(syn::DataEnum { variants, .. }) => variants,
+        _ => {
+            return Err(syn::Error::new(
+                ident.span(),
+                "Variants can only be derived for enums",
+            ))
+        }
+    };
+    if !generics.params.is_empty() {
+        return Err(syn::Error::new(
+            ident.span(),
+            "Variants cannot be derived for generic enums",
+        ));
+    }
+    if let Some(v) = variants
+        .iter()
+        .find(|v| !matches!(v.fields, syn::Fields::Unit))
+    {
+        return Err(syn::Error::new(
+            v.ident.span(),
+            "Variants can only be derived for enums without fields",
+        ));
+    }
+
+    let error_ident = format_ident!("{}ParseError", ident);
+    let error_doc = format!("Error of parsing a `{}` from a string.", ident);
+    let count = variants.len();
+    let names: Vec<&syn::Ident> = variants.iter().map(|v| &v.ident).collect();
+    let strs: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+    let indexes = 0..count;
+
+    Ok(quote! {
+        #[doc = #error_doc]
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct #error_ident(pub std::string::String);
+
+        impl std::fmt::Display for #error_ident {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "'{}' is not a valid {}", self.0, stringify!(#ident))
+            }
+        }
+
+        impl std::error::Error for #error_ident {}
+
+        impl #ident {
+            /// Every variant in the order they were declared.
+            pub const ALL: [#ident; #count] = [#(#ident::#names,)*];
+
+            /// Iterate through every variant in the order they were declared.
+            pub fn iter() -> impl std::iter::Iterator<Item = #ident> {
+                Self::ALL.into_iter()
+            }
+
+            /// Name of the variant.
+            pub fn as_str(&self) -> &'static str {
+                match *self {
+                    #(#ident::#names => #strs,)*
+                }
+            }
+
+            /// Position of the variant in its declaration.
+            pub fn index(&self) -> usize {
+                match *self {
+                    #(#ident::#names => #indexes,)*
+                }
+            }
+
+            /// The variant declared after this one, if there is any.
+            pub fn next(&self) -> std::option::Option<#ident> {
+                Self::iter().nth(self.index() + 1)
+            }
+
+            /// The variant declared before this one, if there is any.
+            pub fn prev(&self) -> std::option::Option<#ident> {
+                self.index().checked_sub(1).and_then(|i| Self::iter().nth(i))
+            }
+        }
+
+        impl std::str::FromStr for #ident {
+            type Err = #error_ident;
+
+            /// Parse a variant by its name, case insensitive.
+            fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+                Self::iter()
+                    .find(|v| v.as_str().eq_ignore_ascii_case(s.trim()))
+                    .ok_or_else(|| #error_ident(s.to_string()))
+            }
+        }
+
+        impl std::cmp::PartialOrd for #ident {
+            fn partial_cmp(&self, other: &Self) -> std::option::Option<std::cmp::Ordering> {
+                std::option::Option::Some(std::cmp::Ord::cmp(self, other))
+            }
+        }
+
+        impl std::cmp::Ord for #ident {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.index().cmp(&other.index())
+            }
+        }
+    })
+}
@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use item_macro::{Builder, Show, Variants};
+
+use crate::game_data::{not_blank, ChaStatus, ChaType, Gender, Item, ItemType, Rarity};
+
+/// Places on a character where an item could be equipped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Show, Variants)]
+pub enum Slot {
+    Head,
+    Body,
+    Hands,
+    Feet,
+    MainHand,
+    OffHand,
+}
+
+impl Slot {
+    /// Whether an item of this type fits in the slot,
+    /// weapons go to either hand and armours go to everywhere else.
+    pub fn accepts(&self, item_type: ItemType) -> bool {
+        match self {
+            Slot::MainHand | Slot::OffHand => item_type == ItemType::Weapon,
+            Slot::Head | Slot::Body | Slot::Hands | Slot::Feet => item_type == ItemType::Armour,
+        }
+    }
+}
+
+/// Error of equipping an item in a slot that does not fit it, the item is handed back.
+#[derive(Debug)]
+pub struct EquipError {
+    pub slot: Slot,
+    pub item: Item,
+}
+
+impl fmt::Display for EquipError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "cannot equip `{}` ({}) in the {} slot",
+            self.item.name(),
+            self.item.item_type().as_str(),
+            self.slot.as_str()
+        )
+    }
+}
+
+impl std::error::Error for EquipError {}
+
+#[allow(dead_code)]
+#[derive(Builder, Debug)]
+#[builder(typestate)]
+pub struct Character {
+    #[builder(into, validate = not_blank)]
+    name: String,
+    #[builder(default)]
+    gender: Gender,
+    #[builder(into, default)]
+    description: String,
+    cha_type: ChaType,
+    rarity: Rarity,
+    /// Status of the character without anything equipped.
+    #[builder(default)]
+    status: ChaStatus,
+    #[builder(skip)]
+    equipment: BTreeMap<Slot, Item>,
+}
+
+#[allow(dead_code)]
+impl Character {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Put an item in a slot, returning whatever was there before.
+    pub fn equip(&mut self, slot: Slot, item: Item) -> Result<Option<Item>, EquipError> {
+        if !slot.accepts(item.item_type()) {
+            return Err(EquipError { slot, item });
+        }
+        Ok(self.equipment.insert(slot, item))
+    }
+
+    /// Take the item off a slot.
+    pub fn unequip(&mut self, slot: Slot) -> Option<Item> {
+        self.equipment.remove(&slot)
+    }
+
+    /// Item equipped in a slot.
+    pub fn equipped(&self, slot: Slot) -> Option<&Item> {
+        self.equipment.get(&slot)
+    }
+
+    /// Status of the character without anything equipped.
+    pub fn base_status(&self) -> &ChaStatus {
+        &self.status
+    }
+
+    /// Status of the character with the bonuses of every equipped item stacked on top,
+    /// see [`ChaStatus::stack`] for how they are added up.
+    pub fn status(&self) -> ChaStatus {
+        self.equipment
+            .values()
+            .fold(self.status, |status, item| status.stack(item.bonus()))
+    }
+}
+
+#[cfg(test)]
+mod character_tests {
+    use super::*;
+
+    fn hero() -> Character {
+        Character::builder()
+            .name("Hero")
+            .cha_type(ChaType::Worrior)
+            .rarity(Rarity::Rare)
+            .status(ChaStatus {
+                max_health: 100,
+                attack: 10,
+                defence: 5,
+                crit_rate: 5,
+                crit_dmg: 50,
+                ..Default::default()
+            })
+            .build()
+            .unwrap()
+    }
+
+    fn item(name: &str, item_type: ItemType, bonus: ChaStatus) -> Item {
+        Item::builder()
+            .name(name)
+            .item_type(item_type)
+            .rarity(Rarity::Normal)
+            .bonus(bonus)
+            .build()
+            .unwrap()
+    }
+
+    fn sword(attack: u32, crit_rate: u8) -> Item {
+        let bonus = ChaStatus {
+            attack,
+            crit_rate,
+            ..Default::default()
+        };
+        item("Sword", ItemType::Weapon, bonus)
+    }
+
+    fn helmet(defence: u32) -> Item {
+        let bonus = ChaStatus {
+            defence,
+            ..Default::default()
+        };
+        item("Helmet", ItemType::Armour, bonus)
+    }
+
+    #[test]
+    fn stacks_equipped_items() {
+        let mut hero = hero();
+        hero.equip(Slot::MainHand, sword(20, 10)).unwrap();
+        hero.equip(Slot::OffHand, sword(5, 0)).unwrap();
+        hero.equip(Slot::Head, helmet(8)).unwrap();
+
+        let status = hero.status();
+        assert_eq!(status.attack, 35);
+        assert_eq!(status.defence, 13);
+        assert_eq!(status.crit_rate, 15);
+        assert_eq!(status.crit_dmg, 50);
+        assert_eq!(status.max_health, 100);
+        // the base status is left untouched
+        assert_eq!(hero.base_status().attack, 10);
+    }
+
+    #[test]
+    fn replace_and_unequip() {
+        let mut hero = hero();
+        assert!(hero.equip(Slot::Head, helmet(8)).unwrap().is_none());
+        let old = hero.equip(Slot::Head, helmet(3)).unwrap();
+        assert_eq!(old.unwrap().bonus().defence, 8);
+        // only the new helmet counts
+        assert_eq!(hero.status().defence, 8);
+
+        assert!(hero.unequip(Slot::Head).is_some());
+        assert!(hero.equipped(Slot::Head).is_none());
+        assert_eq!(hero.status(), *hero.base_status());
+    }
+
+    #[test]
+    fn crit_rate_is_capped() {
+        let mut hero = hero();
+        hero.equip(Slot::MainHand, sword(0, 60)).unwrap();
+        hero.equip(Slot::OffHand, sword(0, 60)).unwrap();
+        assert_eq!(hero.status().crit_rate, ChaStatus::MAX_CRIT_RATE);
+    }
+
+    #[test]
+    fn stats_do_not_overflow() {
+        let mut hero = hero();
+        hero.equip(Slot::MainHand, sword(u32::MAX, 0)).unwrap();
+        assert_eq!(hero.status().attack, u32::MAX);
+    }
+
+    #[test]
+    fn wrong_slot() {
+        let mut hero = hero();
+        let err = hero.equip(Slot::Head, sword(20, 0)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "cannot equip `Sword` (Weapon) in the Head slot"
+        );
+        assert_eq!(err.item.name(), "Sword");
+
+        let potion = item("Potion", ItemType::Consumable, ChaStatus::default());
+        assert!(hero.equip(Slot::MainHand, potion).is_err());
+        assert!(hero.equip(Slot::MainHand, helmet(1)).is_err());
+        assert_eq!(hero.status(), *hero.base_status());
+    }
+}
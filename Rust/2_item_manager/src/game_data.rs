@@ -1,7 +1,8 @@
-use item_macro::{Builder, Show};
+use item_macro::{Builder, Show, Variants};
+use serde::{Deserialize, Serialize};
 
 #[allow(dead_code)]
-#[derive(Clone, Debug, Show)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Show, Variants, Serialize, Deserialize)]
 pub enum ItemType {
     Armour,
     Weapon,
@@ -12,7 +13,7 @@ pub enum ItemType {
 }
 
 #[allow(dead_code)]
-#[derive(Clone, Debug, Show)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Show, Variants, Serialize, Deserialize)]
 pub enum Rarity {
     #[color = "white"]
     Normal,
@@ -27,14 +28,17 @@ pub enum Rarity {
 }
 
 #[allow(dead_code)]
-enum Gender {
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Show)]
+pub enum Gender {
     Male,
     Female,
+    #[default]
     Undefined,
 }
 
 #[allow(dead_code)]
-enum ChaType {
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Show, Variants)]
+pub enum ChaType {
     Worrior,
     Ranger,
     Mage,
@@ -42,34 +46,99 @@ enum ChaType {
     ExoCreature,
 }
 
-#[allow(dead_code)]
-#[derive(Default)]
-struct ChaStatus {
-    max_health: u32,
-    max_stamina: u32,
-    max_mage: u32,
-    attack: u32,
-    defence: u32,
-    crit_rate: u8,
-    crit_dmg: u16,
+/// Status of a character, also used as the bonus an item gives to whoever equips it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Show, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChaStatus {
+    pub max_health: u32,
+    pub max_stamina: u32,
+    pub max_mage: u32,
+    pub attack: u32,
+    pub defence: u32,
+    /// Chance of a critical hit, in percentage.
+    pub crit_rate: u8,
+    /// Extra damage of a critical hit, in percentage.
+    pub crit_dmg: u16,
+}
+
+impl ChaStatus {
+    /// Highest crit rate anyone could reach, no matter how much more is stacked on it.
+    pub const MAX_CRIT_RATE: u8 = 100;
+
+    /// Stack another status on top of this one.
+    ///
+    /// Every stat is added up, the crit rate stops at [`ChaStatus::MAX_CRIT_RATE`]
+    /// and the others stop at their maximum value instead of overflowing.
+    pub fn stack(&self, other: &ChaStatus) -> ChaStatus {
+        ChaStatus {
+            max_health: self.max_health.saturating_add(other.max_health),
+            max_stamina: self.max_stamina.saturating_add(other.max_stamina),
+            max_mage: self.max_mage.saturating_add(other.max_mage),
+            attack: self.attack.saturating_add(other.attack),
+            defence: self.defence.saturating_add(other.defence),
+            crit_rate: self
+                .crit_rate
+                .saturating_add(other.crit_rate)
+                .min(Self::MAX_CRIT_RATE),
+            crit_dmg: self.crit_dmg.saturating_add(other.crit_dmg),
+        }
+    }
 }
 
 #[allow(dead_code)]
-#[derive(Builder, Debug, Show)]
+#[derive(Builder, Clone, Debug, PartialEq, Show, Serialize, Deserialize)]
+#[builder(typestate)]
 pub struct Item {
+    #[builder(into, validate = not_blank)]
     name: String,
+    #[builder(into, default)]
+    #[serde(default)]
     description: String,
     item_type: ItemType,
     rarity: Rarity,
     value: Option<u32>,
+    /// Weight of a single item.
+    #[builder(default)]
+    #[serde(default)]
+    weight: u32,
+    /// Status added to the character equipping this item.
+    #[builder(default)]
+    #[serde(default)]
+    #[show(skip)]
+    bonus: ChaStatus,
 }
 
-#[allow(dead_code)]
-pub struct Character {
-    name: String,
-    gender: Gender,
-    description: String,
-    cha_type: ChaType,
-    rarity: Rarity,
-    status: ChaStatus,
+impl Item {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn item_type(&self) -> ItemType {
+        self.item_type
+    }
+
+    pub fn rarity(&self) -> Rarity {
+        self.rarity
+    }
+
+    pub fn value(&self) -> Option<u32> {
+        self.value
+    }
+
+    pub fn weight(&self) -> u32 {
+        self.weight
+    }
+
+    pub fn bonus(&self) -> &ChaStatus {
+        &self.bonus
+    }
+}
+
+/// Make sure a name has something other than whitespaces.
+pub(crate) fn not_blank(name: &str) -> Result<(), &'static str> {
+    if name.trim().is_empty() {
+        Err("cannot be blank")
+    } else {
+        Ok(())
+    }
 }
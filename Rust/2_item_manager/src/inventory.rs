@@ -0,0 +1,448 @@
+use std::fmt;
+use std::io::{Read, Write};
+use std::ops::RangeBounds;
+
+use item_macro::{Show, Variants};
+use serde::{Deserialize, Serialize};
+
+use crate::game_data::{Item, ItemType, Rarity};
+
+/// Identical items sharing a single slot.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Stack {
+    pub item: Item,
+    pub count: u32,
+}
+
+impl Stack {
+    /// Weight of every item in the stack.
+    pub fn weight(&self) -> u64 {
+        u64::from(self.item.weight()) * u64::from(self.count)
+    }
+
+    /// Value of every item in the stack, items without a value are worth nothing.
+    pub fn value(&self) -> u64 {
+        u64::from(self.item.value().unwrap_or_default()) * u64::from(self.count)
+    }
+}
+
+/// What the stacks of an inventory could be sorted by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Show, Variants)]
+pub enum SortKey {
+    Name,
+    Type,
+    Rarity,
+    Value,
+    Weight,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum InventoryError {
+    /// Every slot is taken and the item does not stack with any of them.
+    NoFreeSlot,
+    /// Adding the items would make the inventory heavier than it could carry.
+    TooHeavy { weight: u64, max_weight: u64 },
+    /// Trying to take more items than there are.
+    NotEnough {
+        name: String,
+        count: u32,
+        available: u32,
+    },
+    /// A stack with no item, it would only take a slot.
+    ZeroCount { name: String },
+    /// A stack holding more items than it could count.
+    TooMany { name: String },
+    /// The same item in more than one stack.
+    DuplicateStack { name: String },
+}
+
+impl fmt::Display for InventoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InventoryError::NoFreeSlot => f.write_str("no free slot left in the inventory"),
+            InventoryError::TooHeavy { weight, max_weight } => write!(
+                f,
+                "inventory would weigh {}, but it can only carry {}",
+                weight, max_weight
+            ),
+            InventoryError::NotEnough {
+                name,
+                count,
+                available,
+            } => write!(
+                f,
+                "cannot take {} `{}`, there is only {}",
+                count, name, available
+            ),
+            InventoryError::ZeroCount { name } => {
+                write!(f, "a stack of `{}` cannot hold 0 items", name)
+            }
+            InventoryError::TooMany { name } => {
+                write!(f, "too many `{}` for a single stack", name)
+            }
+            InventoryError::DuplicateStack { name } => {
+                write!(f, "`{}` is in more than one stack", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InventoryError {}
+
+/// A bag of items, where identical items are stacked in the same slot.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Inventory {
+    max_slots: usize,
+    max_weight: u64,
+    stacks: Vec<Stack>,
+}
+
+impl Inventory {
+    pub fn new(max_slots: usize, max_weight: u64) -> Self {
+        Inventory {
+            max_slots,
+            max_weight,
+            stacks: vec![],
+        }
+    }
+
+    /// Read a saved inventory, making sure it does not hold more than it could
+    /// and that its stacks are what [`Inventory::add`] would have made.
+    pub fn load<R: Read>(reader: R) -> serde_json::Result<Self> {
+        use serde::de::Error;
+
+        let inv: Inventory = serde_json::from_reader(reader)?;
+        for (i, stack) in inv.stacks.iter().enumerate() {
+            let name = stack.item.name().to_string();
+            if stack.count == 0 {
+                return Err(serde_json::Error::custom(InventoryError::ZeroCount {
+                    name,
+                }));
+            }
+            if inv.stacks[..i].iter().any(|s| s.item == stack.item) {
+                return Err(serde_json::Error::custom(InventoryError::DuplicateStack {
+                    name,
+                }));
+            }
+        }
+        if inv.stacks.len() > inv.max_slots {
+            return Err(serde_json::Error::custom(InventoryError::NoFreeSlot));
+        }
+        if inv.weight() > inv.max_weight {
+            return Err(serde_json::Error::custom(InventoryError::TooHeavy {
+                weight: inv.weight(),
+                max_weight: inv.max_weight,
+            }));
+        }
+        Ok(inv)
+    }
+
+    pub fn save<W: Write>(&self, writer: W) -> serde_json::Result<()> {
+        serde_json::to_writer_pretty(writer, self)
+    }
+
+    pub fn stacks(&self) -> &[Stack] {
+        &self.stacks
+    }
+
+    pub fn free_slots(&self) -> usize {
+        self.max_slots.saturating_sub(self.stacks.len())
+    }
+
+    pub fn weight(&self) -> u64 {
+        self.stacks.iter().map(Stack::weight).sum()
+    }
+
+    pub fn total_value(&self) -> u64 {
+        self.stacks.iter().map(Stack::value).sum()
+    }
+
+    /// Number of an item in the inventory.
+    pub fn count(&self, item: &Item) -> u32 {
+        self.stacks
+            .iter()
+            .find(|s| s.item == *item)
+            .map(|s| s.count)
+            .unwrap_or_default()
+    }
+
+    /// Put some copies of an item in the inventory, either on top of the stack of the same item
+    /// or in a free slot. Nothing is added if they don't fit.
+    pub fn add(&mut self, item: Item, count: u32) -> Result<(), InventoryError> {
+        if count == 0 {
+            return Err(InventoryError::ZeroCount {
+                name: item.name().to_string(),
+            });
+        }
+        let weight = self.weight() + u64::from(item.weight()) * u64::from(count);
+        if weight > self.max_weight {
+            return Err(InventoryError::TooHeavy {
+                weight,
+                max_weight: self.max_weight,
+            });
+        }
+
+        if let Some(stack) = self.stacks.iter_mut().find(|s| s.item == item) {
+            stack.count =
+                stack
+                    .count
+                    .checked_add(count)
+                    .ok_or_else(|| InventoryError::TooMany {
+                        name: item.name().to_string(),
+                    })?;
+        } else if self.stacks.len() < self.max_slots {
+            self.stacks.push(Stack { item, count });
+        } else {
+            return Err(InventoryError::NoFreeSlot);
+        }
+        Ok(())
+    }
+
+    /// Take some copies of an item out of the inventory, freeing its slot if none is left.
+    pub fn remove(&mut self, item: &Item, count: u32) -> Result<(), InventoryError> {
+        let available = self.count(item);
+        if available < count {
+            return Err(InventoryError::NotEnough {
+                name: item.name().to_string(),
+                count,
+                available,
+            });
+        }
+        if available == count {
+            self.stacks.retain(|s| s.item != *item);
+        } else if let Some(stack) = self.stacks.iter_mut().find(|s| s.item == *item) {
+            stack.count -= count;
+        }
+        Ok(())
+    }
+
+    /// Stacks of a type of item.
+    pub fn of_type(&self, item_type: ItemType) -> impl Iterator<Item = &Stack> {
+        self.stacks
+            .iter()
+            .filter(move |s| s.item.item_type() == item_type)
+    }
+
+    /// Stacks of items that are at least as rare as `rarity`.
+    pub fn at_least(&self, rarity: Rarity) -> impl Iterator<Item = &Stack> {
+        self.stacks
+            .iter()
+            .filter(move |s| s.item.rarity() >= rarity)
+    }
+
+    /// Stacks of items that are worth a value within `range`, per item.
+    pub fn valued<R: RangeBounds<u32>>(&self, range: R) -> impl Iterator<Item = &Stack> {
+        self.stacks
+            .iter()
+            .filter(move |s| s.item.value().is_some_and(|v| range.contains(&v)))
+    }
+
+    /// Sort the stacks in ascending order, stacks that are equal keep their order.
+    pub fn sort_by(&mut self, key: SortKey) {
+        match key {
+            SortKey::Name => self.stacks.sort_by(|a, b| a.item.name().cmp(b.item.name())),
+            SortKey::Type => self.stacks.sort_by_key(|s| s.item.item_type()),
+            SortKey::Rarity => self.stacks.sort_by_key(|s| s.item.rarity()),
+            SortKey::Value => self.stacks.sort_by_key(|s| s.item.value()),
+            SortKey::Weight => self.stacks.sort_by_key(|s| s.item.weight()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod inventory_tests {
+    use super::*;
+
+    fn item(name: &str, item_type: ItemType, rarity: Rarity, value: u32, weight: u32) -> Item {
+        Item::builder()
+            .name(name)
+            .item_type(item_type)
+            .rarity(rarity)
+            .value(value)
+            .weight(weight)
+            .build()
+            .unwrap()
+    }
+
+    fn potion() -> Item {
+        item("Potion", ItemType::Consumable, Rarity::Normal, 50, 1)
+    }
+
+    fn sword() -> Item {
+        item("Sword", ItemType::Weapon, Rarity::Rare, 1200, 10)
+    }
+
+    fn crown() -> Item {
+        item("Crown", ItemType::Tradable, Rarity::Legendary, 90000, 3)
+    }
+
+    fn bag() -> Inventory {
+        let mut inv = Inventory::new(5, 100);
+        inv.add(potion(), 10).unwrap();
+        inv.add(sword(), 1).unwrap();
+        inv.add(crown(), 1).unwrap();
+        inv
+    }
+
+    #[test]
+    fn stack_identical_items() {
+        let mut inv = bag();
+        inv.add(potion(), 5).unwrap();
+        assert_eq!(inv.stacks().len(), 3);
+        assert_eq!(inv.count(&potion()), 15);
+
+        // same name, but not the same item
+        let cheap_potion = item("Potion", ItemType::Consumable, Rarity::Normal, 5, 1);
+        inv.add(cheap_potion.clone(), 1).unwrap();
+        assert_eq!(inv.stacks().len(), 4);
+        assert_eq!(inv.count(&cheap_potion), 1);
+    }
+
+    #[test]
+    fn slot_capacity() {
+        let mut inv = Inventory::new(1, 100);
+        inv.add(potion(), 1).unwrap();
+        assert_eq!(inv.add(sword(), 1), Err(InventoryError::NoFreeSlot));
+        // stacking does not need another slot
+        inv.add(potion(), 1).unwrap();
+        assert_eq!(inv.free_slots(), 0);
+    }
+
+    #[test]
+    fn weight_capacity() {
+        let mut inv = bag();
+        assert_eq!(inv.weight(), 23);
+        assert_eq!(
+            inv.add(sword(), 8),
+            Err(InventoryError::TooHeavy {
+                weight: 103,
+                max_weight: 100
+            })
+        );
+        assert_eq!(inv.count(&sword()), 1);
+        inv.add(sword(), 7).unwrap();
+        assert_eq!(inv.weight(), 93);
+    }
+
+    #[test]
+    fn reject_bad_counts() {
+        let mut inv = bag();
+        assert_eq!(
+            inv.add(sword(), 0),
+            Err(InventoryError::ZeroCount {
+                name: String::from("Sword")
+            })
+        );
+        assert_eq!(
+            inv.add(potion(), 0).unwrap_err().to_string(),
+            "a stack of `Potion` cannot hold 0 items"
+        );
+        assert_eq!(inv.stacks().len(), 3);
+
+        // weightless items never hit the weight limit, but a stack can only count so many
+        let feather = item("Feather", ItemType::Material, Rarity::Normal, 1, 0);
+        inv.add(feather.clone(), u32::MAX).unwrap();
+        assert_eq!(
+            inv.add(feather.clone(), 1),
+            Err(InventoryError::TooMany {
+                name: String::from("Feather")
+            })
+        );
+        assert_eq!(inv.count(&feather), u32::MAX);
+    }
+
+    #[test]
+    fn remove_items() {
+        let mut inv = bag();
+        inv.remove(&potion(), 4).unwrap();
+        assert_eq!(inv.count(&potion()), 6);
+        let err = inv.remove(&potion(), 7).unwrap_err();
+        assert_eq!(err.to_string(), "cannot take 7 `Potion`, there is only 6");
+        inv.remove(&potion(), 6).unwrap();
+        assert_eq!(inv.stacks().len(), 2);
+        assert_eq!(inv.free_slots(), 3);
+    }
+
+    #[test]
+    fn queries() {
+        let inv = bag();
+        let names = |stacks: Vec<&Stack>| -> Vec<String> {
+            stacks.iter().map(|s| s.item.name().to_string()).collect()
+        };
+        assert_eq!(
+            names(inv.of_type(ItemType::Weapon).collect()),
+            vec!["Sword"]
+        );
+        assert_eq!(
+            names(inv.at_least(Rarity::Rare).collect()),
+            vec!["Sword", "Crown"]
+        );
+        assert_eq!(
+            names(inv.valued(..=1200).collect()),
+            vec!["Potion", "Sword"]
+        );
+        assert_eq!(names(inv.valued(1000..).collect()), vec!["Sword", "Crown"]);
+        assert_eq!(inv.total_value(), 500 + 1200 + 90000);
+    }
+
+    #[test]
+    fn sorting() {
+        let mut inv = bag();
+        let names = |inv: &Inventory| -> Vec<String> {
+            inv.stacks()
+                .iter()
+                .map(|s| s.item.name().to_string())
+                .collect()
+        };
+        inv.sort_by(SortKey::Name);
+        assert_eq!(names(&inv), vec!["Crown", "Potion", "Sword"]);
+        inv.sort_by(SortKey::Rarity);
+        assert_eq!(names(&inv), vec!["Potion", "Sword", "Crown"]);
+        inv.sort_by(SortKey::Weight);
+        assert_eq!(names(&inv), vec!["Potion", "Crown", "Sword"]);
+    }
+
+    #[test]
+    fn save_round_trip() {
+        let inv = bag();
+        let mut saved = vec![];
+        inv.save(&mut saved).unwrap();
+        assert_eq!(Inventory::load(saved.as_slice()).unwrap(), inv);
+    }
+
+    #[test]
+    fn load_over_capacity() {
+        let mut inv = bag();
+        inv.max_weight = 10;
+        let mut saved = vec![];
+        inv.save(&mut saved).unwrap();
+        let err = Inventory::load(saved.as_slice()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "inventory would weigh 23, but it can only carry 10"
+        );
+    }
+
+    #[test]
+    fn load_bad_stacks() {
+        let load = |inv: &Inventory| {
+            let mut saved = vec![];
+            inv.save(&mut saved).unwrap();
+            Inventory::load(saved.as_slice()).map_err(|e| e.to_string())
+        };
+
+        let mut inv = bag();
+        inv.stacks[1].count = 0;
+        assert_eq!(
+            load(&inv).unwrap_err(),
+            "a stack of `Sword` cannot hold 0 items"
+        );
+
+        let mut inv = bag();
+        inv.stacks.push(inv.stacks[0].clone());
+        assert_eq!(
+            load(&inv).unwrap_err(),
+            "`Potion` is in more than one stack"
+        );
+    }
+}
@@ -1,22 +1,29 @@
+mod character;
 mod game_data;
+mod inventory;
 
-use game_data::{Item, ItemType, Rarity};
+use character::{Character, Slot};
+use game_data::{ChaStatus, ChaType, Item, ItemType, Rarity};
+use inventory::{Inventory, SortKey};
 
 fn main() {
     let normal_item = Item::builder()
-        .name(String::from("Rusted Sword"))
-        .description(String::from(
-            "Not so sharp, but may causing periodic damages.",
-        ))
+        .name("Rusted Sword")
+        .description("Not so sharp, but may causing periodic damages.")
         .item_type(ItemType::Weapon)
         .rarity(Rarity::Normal)
         .value(300)
+        .bonus(ChaStatus {
+            attack: 12,
+            crit_rate: 5,
+            ..Default::default()
+        })
         .build()
         .unwrap();
 
     let legend_item = Item::builder()
-        .name(String::from("Heart of Elder Dragon"))
-        .description(String::from("Legend says, before the dragon slayer killed a dragon, they will ripped the heart of the dragon and make it as souvenir."))
+        .name("Heart of Elder Dragon")
+        .description("Legend says, before the dragon slayer killed a dragon, they will ripped the heart of the dragon and make it as souvenir.")
         .item_type(ItemType::Material)
         .rarity(Rarity::Legendary)
         .value(8_000_000)
@@ -29,9 +36,71 @@ fn main() {
 
     let sep_line = (0..50).map(|_| "-").collect::<String>();
     println!(
-        "Displaying normal item: \n{1}\n{0}{1}",
+        "Displaying normal item: \n{1}\n{0:#}\n{1}",
         normal_item, sep_line
     );
 
     println!("Displaying Rarity enum: \n{}", Rarity::Legendary);
+
+    let rare: Rarity = "rare".parse().unwrap();
+    let tiers: Vec<String> = Rarity::iter().map(|r| r.to_string()).collect();
+    println!("Rarity tiers: {}", tiers.join(" < "));
+    if let Some(upgraded) = rare.next() {
+        println!("Upgrading a {} item makes it {}", rare, upgraded);
+    }
+
+    let mut bag = Inventory::new(20, 500);
+    bag.add(legend_item.clone(), 1).unwrap();
+    bag.add(normal_item.clone(), 2).unwrap();
+    bag.sort_by(SortKey::Value);
+    for stack in bag.stacks() {
+        println!("{} x{}", stack.item.name(), stack.count);
+    }
+    println!(
+        "Total value of the bag: {}, with {} rare or better",
+        bag.total_value(),
+        bag.at_least(Rarity::Rare).count()
+    );
+    println!(
+        "{} weapon(s), {} item(s) worth 1000 or more",
+        bag.of_type(ItemType::Weapon).count(),
+        bag.valued(1000..).count()
+    );
+    bag.remove(&normal_item, 1).unwrap();
+    println!(
+        "Sold one {}, {} left and {} free slots",
+        normal_item.name(),
+        bag.count(&normal_item),
+        bag.free_slots()
+    );
+    let mut saved = vec![];
+    bag.save(&mut saved).unwrap();
+    assert_eq!(Inventory::load(saved.as_slice()).unwrap(), bag);
+    println!("Saved the bag in {} bytes", saved.len());
+
+    let mut hero = Character::builder()
+        .name("Wanderer")
+        .cha_type(ChaType::Worrior)
+        .rarity(Rarity::Rare)
+        .status(ChaStatus {
+            max_health: 120,
+            max_stamina: 80,
+            attack: 15,
+            defence: 10,
+            crit_rate: 5,
+            crit_dmg: 50,
+            ..Default::default()
+        })
+        .build()
+        .unwrap();
+    if let Err(e) = hero.equip(Slot::Head, legend_item) {
+        println!("{}", e);
+    }
+    hero.equip(Slot::MainHand, normal_item).unwrap();
+    println!(
+        "Status of {} with {} equipped: \n{:#}",
+        hero.name(),
+        Slot::MainHand,
+        hero.status()
+    );
 }
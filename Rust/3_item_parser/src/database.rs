@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::game_data::{Items, Rarity, CATEGORIES};
+use crate::query::{self, Entry, Listed};
+use crate::validate::{self, LoadError};
+
+/// Where an entry is, its category and its index in the list of that category.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Location {
+    category: &'static str,
+    index: usize,
+}
+
+/// Parsed data file with indexes to look up its entries by name, rarity and category.
+#[derive(Debug)]
+pub struct ItemDb {
+    items: Items,
+    /// Lowercased name -> entry.
+    by_name: HashMap<String, Location>,
+    by_rarity: HashMap<Rarity, Vec<Location>>,
+}
+
+impl ItemDb {
+    pub fn new(items: Items) -> Self {
+        let mut by_name = HashMap::new();
+        let mut by_rarity: HashMap<Rarity, Vec<Location>> = HashMap::new();
+        for category in CATEGORIES {
+            for (index, listed) in query::category(&items, category)
+                .unwrap_or_default()
+                .into_iter()
+                .enumerate()
+            {
+                let location = Location { category, index };
+                // names are unique in a validated file, keep the first one otherwise
+                by_name
+                    .entry(listed.entry.name().to_lowercase())
+                    .or_insert(location);
+                by_rarity
+                    .entry(listed.entry.rarity())
+                    .or_default()
+                    .push(location);
+            }
+        }
+        ItemDb {
+            items,
+            by_name,
+            by_rarity,
+        }
+    }
+
+    /// Read, validate and index a data file, see [`validate::load`].
+    pub fn load(path: &Path) -> Result<Self, LoadError> {
+        validate::load(path).map(ItemDb::new)
+    }
+
+    pub fn items(&self) -> &Items {
+        &self.items
+    }
+
+    pub fn into_items(self) -> Items {
+        self.items
+    }
+
+    /// Number of entries in every category.
+    pub fn len(&self) -> usize {
+        self.by_rarity.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Find an entry by its name, case insensitive.
+    pub fn get(&self, name: &str) -> Option<Listed<'_>> {
+        let location = self.by_name.get(&name.trim().to_lowercase())?;
+        Some(self.resolve(*location))
+    }
+
+    /// Entries of a rarity, in the order of their categories then the order in the file.
+    pub fn by_rarity(&self, rarity: Rarity) -> impl Iterator<Item = Listed<'_>> {
+        self.by_rarity
+            .get(&rarity)
+            .into_iter()
+            .flatten()
+            .map(|location| self.resolve(*location))
+    }
+
+    /// Entries of a category, nothing if there is no such category.
+    pub fn by_category(&self, category: &str) -> Vec<Listed<'_>> {
+        query::category(&self.items, category).unwrap_or_default()
+    }
+
+    /// Names of the entries of a rarity, optionally only of a category,
+    /// e.g. to fill a tier of a gacha pool.
+    pub fn names(&self, rarity: Rarity, category: Option<&str>) -> Vec<&str> {
+        self.by_rarity(rarity)
+            .filter(|l| category.is_none_or(|c| c == l.category))
+            .map(|l| l.entry.name())
+            .collect()
+    }
+
+    fn resolve(&self, location: Location) -> Listed<'_> {
+        let Location { category, index } = location;
+        let entry = match (category, self.items.category(category)) {
+            (_, Some(list)) => Entry::Item(&list[index]),
+            ("artifacts", None) => Entry::Artifact(&self.items.artifacts()[index]),
+            _ => Entry::Character(&self.items.characters()[index]),
+        };
+        Listed { category, entry }
+    }
+}
+
+impl From<Items> for ItemDb {
+    fn from(items: Items) -> Self {
+        ItemDb::new(items)
+    }
+}
+
+#[cfg(test)]
+mod database_tests {
+    use super::*;
+
+    fn db() -> ItemDb {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("res/items.json");
+        ItemDb::load(&path).unwrap()
+    }
+
+    #[test]
+    fn lookup_by_name() {
+        let db = db();
+        let sword = db.get("iron SWORD").unwrap();
+        assert_eq!(sword.category, "weapons");
+        match sword.entry {
+            Entry::Item(item) => {
+                assert_eq!(item.name(), "Iron Sword");
+                assert_eq!(item.weapon_attr().unwrap().damage(), 10);
+            }
+            _ => panic!("Iron Sword should be an item"),
+        }
+        assert_eq!(db.get("Mona").unwrap().category, "characters");
+        let json = serde_json::to_value(db.get("Mona")).unwrap();
+        assert_eq!(
+            (&json["category"], &json["cha_type"]),
+            (&"characters".into(), &"Mage".into())
+        );
+        assert!(db.get("Excalibur").is_none());
+        assert_eq!(db.len(), 18);
+    }
+
+    #[test]
+    fn lookup_by_rarity() {
+        let db = db();
+        assert_eq!(
+            db.names(Rarity::Legendary, None),
+            vec!["Mona", "Demon Sword", "Stinger of Python"]
+        );
+        assert_eq!(
+            db.names(Rarity::Legendary, Some("characters")),
+            vec!["Mona"]
+        );
+        assert_eq!(
+            db.names(Rarity::Normal, Some("armours")),
+            vec!["Leather Helmet"]
+        );
+        assert!(db.names(Rarity::Epic, Some("armours")).is_empty());
+        assert!(db
+            .by_rarity(Rarity::Rare)
+            .all(|l| l.entry.rarity() == Rarity::Rare));
+    }
+
+    #[test]
+    fn lookup_by_category() {
+        let db = db();
+        assert_eq!(db.by_category("weapons").len(), 10);
+        assert_eq!(db.by_category("characters").len(), 1);
+        assert_eq!(db.by_category("artifacts").len(), 6);
+        assert!(db.by_category("materials").is_empty());
+        assert!(db.by_category("potions").is_empty());
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::game_data::{Items, ARTIFACT_SETS};
+use crate::query;
+
+/// A difference between two data files.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum Change {
+    Added {
+        category: &'static str,
+        name: String,
+    },
+    Removed {
+        category: &'static str,
+        name: String,
+    },
+    /// A field of an entry found in both files by its name, nested fields are written as
+    /// `parent.field`. A missing field is `null`.
+    Modified {
+        name: String,
+        field: String,
+        old: Value,
+        new: Value,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::Added { category, name } => write!(f, "+ {} ({})", name, category),
+            Change::Removed { category, name } => write!(f, "- {} ({})", name, category),
+            Change::Modified {
+                name,
+                field,
+                old,
+                new,
+            } => write!(f, "~ {}.{}: {} -> {}", name, field, old, new),
+        }
+    }
+}
+
+/// Every difference between two data files, the removed and modified entries in the order of
+/// the old file, then the added ones in the order of the new file.
+///
+/// Entries are matched by their name, an entry moved to another category is modified,
+/// its `category` changed. Artifact sets are compared on their own.
+pub fn diff(old: &Items, new: &Items) -> Vec<Change> {
+    let mut changes = compare(entries(old), entries(new));
+    changes.extend(compare(sets(old), sets(new)));
+    changes
+}
+
+/// Category, name and content of every entry.
+type Named<'a> = Vec<(&'static str, &'a str, Value)>;
+
+fn entries(items: &Items) -> Named<'_> {
+    query::entries(items)
+        .into_iter()
+        .map(|l| {
+            let value = serde_json::to_value(&l).expect("entries are plain data");
+            (l.category, l.entry.name(), value)
+        })
+        .collect()
+}
+
+fn sets(items: &Items) -> Named<'_> {
+    items
+        .artifact_sets()
+        .iter()
+        .map(|set| {
+            let value = serde_json::to_value(set).expect("sets are plain data");
+            (ARTIFACT_SETS, set.name.as_str(), value)
+        })
+        .collect()
+}
+
+fn compare(old: Named, new: Named) -> Vec<Change> {
+    let mut changes = vec![];
+    let new_by_name: HashMap<&str, &Value> = new.iter().map(|(_, n, v)| (*n, v)).collect();
+    for (category, name, value) in &old {
+        match new_by_name.get(name) {
+            Some(new_value) => compare_fields(name, String::new(), value, new_value, &mut changes),
+            None => changes.push(Change::Removed {
+                category,
+                name: name.to_string(),
+            }),
+        }
+    }
+    let old_names: Vec<&str> = old.iter().map(|(_, n, _)| *n).collect();
+    for (category, name, _) in &new {
+        if !old_names.contains(name) {
+            changes.push(Change::Added {
+                category,
+                name: name.to_string(),
+            });
+        }
+    }
+    changes
+}
+
+/// Compare two values of a field, going into the fields of both if they are objects.
+fn compare_fields(name: &str, field: String, old: &Value, new: &Value, changes: &mut Vec<Change>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let mut keys: Vec<&String> = old.keys().collect();
+            keys.extend(new.keys().filter(|k| !old.contains_key(*k)));
+            for key in keys {
+                let field = if field.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", field, key)
+                };
+                let old = old.get(key).unwrap_or(&Value::Null);
+                let new = new.get(key).unwrap_or(&Value::Null);
+                compare_fields(name, field, old, new, changes);
+            }
+        }
+        _ if old != new => changes.push(Change::Modified {
+            name: name.to_string(),
+            field,
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod diff_tests {
+    use super::*;
+    use crate::format::Format;
+    use std::path::Path;
+
+    fn items(src: &str) -> Items {
+        crate::validate::parse(Path::new("items.json"), src, Format::Json).unwrap()
+    }
+
+    #[test]
+    fn nothing_changed() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("res/items.json");
+        let items = crate::validate::load(&path).unwrap();
+        assert!(diff(&items, &items).is_empty());
+    }
+
+    #[test]
+    fn field_changes() {
+        let old = items(
+            r#"{
+            "weapons": [
+                { "name": "Iron Sword", "description": "", "rarity": "Normal", "value": 100,
+                  "weapon_attr": { "level": 0, "damage": 10, "ex_damage": 0, "ex_duration": 0, "crit_rate": 0, "crit_dmg": 0 } }
+            ],
+            "armours": [{ "name": "Wooden Shield", "description": "", "rarity": "Normal" }],
+            "materials": [{ "name": "Pebble", "description": "", "rarity": "Normal" }],
+            "artifact_sets": [{ "name": "Berserker", "two_piece": { "damage": 20 } }]
+        }"#,
+        );
+        let new = items(
+            r#"{
+            "version": 2,
+            "weapons": [
+                { "name": "Iron Sword", "description": "", "rarity": "Special", "value": 100,
+                  "weapon_attr": { "level": 0, "damage": 12, "ex_damage": 0, "ex_duration": 0, "crit_rate": 0, "crit_dmg": 0 } }
+            ],
+            "armours": [{ "name": "Fire Shield", "description": "", "rarity": "Epic" }],
+            "tradable": [{ "name": "Pebble", "description": "", "rarity": "Normal", "value": 1 }],
+            "artifact_sets": [{ "name": "Berserker", "two_piece": { "damage": 25 } }]
+        }"#,
+        );
+        let changes: Vec<String> = diff(&old, &new).iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            changes,
+            vec![
+                "- Wooden Shield (armours)",
+                "~ Pebble.category: \"materials\" -> \"tradable\"",
+                "~ Pebble.value: null -> 1",
+                "~ Iron Sword.rarity: \"Normal\" -> \"Special\"",
+                "~ Iron Sword.weapon_attr.damage: 10 -> 12",
+                "+ Fire Shield (armours)",
+                "~ Berserker.two_piece.damage: 20 -> 25",
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&diff(&old, &new)[0]).unwrap(),
+            serde_json::json!({ "change": "removed", "category": "armours", "name": "Wooden Shield" })
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Formats a data file could be written in.
+///
+/// Every format other than JSON is behind a cargo feature of the same name (all enabled by
+/// default), a format that is not enabled is still recognized but fails to read or write.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Toml,
+    Yaml,
+    Ron,
+}
+
+impl Format {
+    pub const ALL: [Format; 4] = [Format::Json, Format::Toml, Format::Yaml, Format::Ron];
+
+    /// Guess the format of a file by its extension.
+    pub fn from_path(path: &Path) -> Option<Format> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "json" => Some(Format::Json),
+            "toml" => Some(Format::Toml),
+            "yaml" | "yml" => Some(Format::Yaml),
+            "ron" => Some(Format::Ron),
+            _ => None,
+        }
+    }
+
+    /// Whether the cargo feature of the format is enabled.
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            Format::Json => true,
+            Format::Toml => cfg!(feature = "toml"),
+            Format::Yaml => cfg!(feature = "yaml"),
+            Format::Ron => cfg!(feature = "ron"),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Toml => "toml",
+            Format::Yaml => "yaml",
+            Format::Ron => "ron",
+        }
+    }
+
+    /// Read a value from the content of a file in this format.
+    pub fn deserialize<T: DeserializeOwned>(&self, src: &str) -> Result<T, String> {
+        self.check_enabled()?;
+        match self {
+            Format::Json => serde_json::from_str(src).map_err(|e| e.to_string()),
+            #[cfg(feature = "toml")]
+            Format::Toml => toml::from_str(src).map_err(|e| e.to_string()),
+            #[cfg(feature = "yaml")]
+            Format::Yaml => serde_yaml::from_str(src).map_err(|e| e.to_string()),
+            #[cfg(feature = "ron")]
+            Format::Ron => ron::from_str(src).map_err(|e| e.to_string()),
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("{} is not enabled", self),
+        }
+    }
+
+    /// Write a value in this format.
+    pub fn serialize<T: Serialize>(&self, value: &T) -> Result<String, String> {
+        self.check_enabled()?;
+        match self {
+            Format::Json => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+            #[cfg(feature = "toml")]
+            Format::Toml => {
+                // `toml::Value` puts plain values before tables, which TOML requires
+                toml::Value::try_from(value)
+                    .and_then(|v| toml::to_string_pretty(&v))
+                    .map_err(|e| e.to_string())
+            }
+            #[cfg(feature = "yaml")]
+            Format::Yaml => serde_yaml::to_string(value).map_err(|e| e.to_string()),
+            #[cfg(feature = "ron")]
+            Format::Ron => ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::new())
+                .map_err(|e| e.to_string()),
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("{} is not enabled", self),
+        }
+    }
+
+    fn check_enabled(&self) -> Result<(), String> {
+        if self.is_enabled() {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} support is not enabled, rebuild with the `{}` feature",
+                self.as_str().to_uppercase(),
+                self.as_str()
+            ))
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Format::ALL
+            .into_iter()
+            .find(|f| f.as_str().eq_ignore_ascii_case(s) || (s == "yml" && *f == Format::Yaml))
+            .ok_or_else(|| format!("unknown format `{}`, expected json, toml, yaml or ron", s))
+    }
+}
+
+/// Translate the content of a file from a format to another.
+///
+/// The content goes through a [`serde_json::Value`], so anything is kept as long as the target
+/// format could hold it, e.g. TOML has no `null` and needs a table at the top.
+pub fn convert(src: &str, from: Format, to: Format) -> Result<String, String> {
+    let value: serde_json::Value = from.deserialize(src)?;
+    to.serialize(&value)
+}
+
+#[cfg(test)]
+mod format_tests {
+    use super::*;
+
+    fn res(name: &str) -> String {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("res").join(name);
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    /// Convert a JSON file to a format and back, it should be the same as the original.
+    fn round_trip(name: &str, format: Format) {
+        let src = res(name);
+        let converted = convert(&src, Format::Json, format).unwrap();
+        let back = convert(&converted, format, Format::Json).unwrap();
+        let original: serde_json::Value = serde_json::from_str(&src).unwrap();
+        let back: serde_json::Value = serde_json::from_str(&back).unwrap();
+        assert_eq!(original, back, "{} through {}", name, format);
+    }
+
+    #[test]
+    fn detect_format() {
+        assert_eq!(
+            Format::from_path(Path::new("a/items.json")),
+            Some(Format::Json)
+        );
+        assert_eq!(
+            Format::from_path(Path::new("items.YML")),
+            Some(Format::Yaml)
+        );
+        assert_eq!(Format::from_path(Path::new("items.ron")), Some(Format::Ron));
+        assert_eq!(Format::from_path(Path::new("items.txt")), None);
+        assert_eq!(Format::from_path(Path::new("items")), None);
+        assert_eq!("TOML".parse(), Ok(Format::Toml));
+        assert!("xml".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn json_round_trip() {
+        round_trip("items.json", Format::Json);
+        round_trip("test.json", Format::Json);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_round_trip() {
+        round_trip("items.json", Format::Toml);
+        round_trip("test.json", Format::Toml);
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn yaml_round_trip() {
+        round_trip("items.json", Format::Yaml);
+        round_trip("test.json", Format::Yaml);
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn ron_round_trip() {
+        round_trip("items.json", Format::Ron);
+        round_trip("test.json", Format::Ron);
+    }
+}
@@ -1,19 +1,67 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Keys of the data file, each one holding a list of items (or characters, or artifacts).
+pub const CATEGORIES: [&str; 7] = [
+    "armours",
+    "artifacts",
+    "characters",
+    "consumables",
+    "materials",
+    "tradable",
+    "weapons",
+];
+
+/// Key of the data file holding the definitions of the artifact sets.
+pub const ARTIFACT_SETS: &str = "artifact_sets";
 
-#[allow(dead_code)]
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Items {
     pub armours: Option<Vec<Item>>,
-    pub artifacts: Option<Vec<Item>>,
+    pub artifacts: Option<Vec<Artifact>>,
+    pub artifact_sets: Option<Vec<ArtifactSet>>,
     pub characters: Option<Vec<Character>>,
-    pub consumable: Option<Vec<Item>>,
+    pub consumables: Option<Vec<Item>>,
     pub materials: Option<Vec<Item>>,
     pub tradable: Option<Vec<Item>>,
     pub weapons: Option<Vec<Item>>,
 }
 
-#[allow(dead_code)]
-#[derive(Clone, Debug, Serialize, Deserialize)]
+impl Items {
+    /// Items of a category, empty if the category is missing from the file.
+    /// `None` if it is not a category of items, such as `characters` or `artifacts`.
+    pub fn category(&self, category: &str) -> Option<&[Item]> {
+        let list = match category {
+            "armours" => &self.armours,
+            "consumables" => &self.consumables,
+            "materials" => &self.materials,
+            "tradable" => &self.tradable,
+            "weapons" => &self.weapons,
+            _ => return None,
+        };
+        Some(list.as_deref().unwrap_or_default())
+    }
+
+    pub fn characters(&self) -> &[Character] {
+        self.characters.as_deref().unwrap_or_default()
+    }
+
+    pub fn artifacts(&self) -> &[Artifact] {
+        self.artifacts.as_deref().unwrap_or_default()
+    }
+
+    pub fn artifact_sets(&self) -> &[ArtifactSet] {
+        self.artifact_sets.as_deref().unwrap_or_default()
+    }
+
+    /// Find the definition of an artifact set by its name.
+    pub fn artifact_set(&self, name: &str) -> Option<&ArtifactSet> {
+        self.artifact_sets().iter().find(|set| set.name == name)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Rarity {
     Normal,
     Special,
@@ -22,17 +70,53 @@ pub enum Rarity {
     Legendary,
 }
 
-#[allow(dead_code)]
-#[derive(Serialize, Deserialize, Debug)]
-enum Gender {
+impl Rarity {
+    pub const ALL: [Rarity; 5] = [
+        Rarity::Normal,
+        Rarity::Special,
+        Rarity::Rare,
+        Rarity::Epic,
+        Rarity::Legendary,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Rarity::Normal => "Normal",
+            Rarity::Special => "Special",
+            Rarity::Rare => "Rare",
+            Rarity::Epic => "Epic",
+            Rarity::Legendary => "Legendary",
+        }
+    }
+}
+
+impl fmt::Display for Rarity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Rarity {
+    type Err = String;
+
+    /// Parse a rarity by its name, case insensitive.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Rarity::ALL
+            .into_iter()
+            .find(|r| r.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown rarity `{}`", s))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Gender {
     Male,
     Female,
     Undefined,
 }
 
-#[allow(dead_code)]
-#[derive(Serialize, Deserialize, Debug)]
-enum ChaType {
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChaType {
     Worrior,
     Ranger,
     Mage,
@@ -40,9 +124,8 @@ enum ChaType {
     Demon,
 }
 
-#[allow(dead_code)]
 #[derive(Default, Serialize, Deserialize, Debug)]
-struct WeaponStatus {
+pub struct WeaponStatus {
     level: u8,
     damage: u32,
     ex_damage: u32,
@@ -51,9 +134,34 @@ struct WeaponStatus {
     crit_dmg: u16,
 }
 
-#[allow(dead_code)]
+impl WeaponStatus {
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn damage(&self) -> u32 {
+        self.damage
+    }
+
+    pub fn ex_damage(&self) -> u32 {
+        self.ex_damage
+    }
+
+    pub fn ex_duration(&self) -> u8 {
+        self.ex_duration
+    }
+
+    pub fn crit_rate(&self) -> u8 {
+        self.crit_rate
+    }
+
+    pub fn crit_dmg(&self) -> u16 {
+        self.crit_dmg
+    }
+}
+
 #[derive(Default, Serialize, Deserialize, Debug)]
-struct ArmourStatus {
+pub struct ArmourStatus {
     level: u8,
     health: u32,
     defence: u32,
@@ -66,8 +174,49 @@ struct ArmourStatus {
     mag_resist: u8,
 }
 
-#[allow(dead_code)]
-#[derive(Serialize, Deserialize, Debug)]
+impl ArmourStatus {
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn health(&self) -> u32 {
+        self.health
+    }
+
+    pub fn defence(&self) -> u32 {
+        self.defence
+    }
+
+    pub fn damage(&self) -> u32 {
+        self.damage
+    }
+
+    pub fn stamina(&self) -> u32 {
+        self.stamina
+    }
+
+    pub fn mana(&self) -> u32 {
+        self.mana
+    }
+
+    pub fn crit_rate(&self) -> u8 {
+        self.crit_rate
+    }
+
+    pub fn crit_dmg(&self) -> u16 {
+        self.crit_dmg
+    }
+
+    pub fn phy_resist(&self) -> u8 {
+        self.phy_resist
+    }
+
+    pub fn mag_resist(&self) -> u8 {
+        self.mag_resist
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum Position {
     Head,
     Chest,
@@ -79,21 +228,125 @@ pub enum Position {
     Necklace,
 }
 
-#[allow(dead_code)]
+/// Stats an artifact gives, or the bonus of an artifact set.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArtifactStatus {
+    pub damage: u32,
+    pub health: u32,
+    pub defence: u32,
+    pub crit_rate: u8,
+    pub crit_dmg: u16,
+    pub phy_resist: u8,
+    pub mag_resist: u8,
+}
+
+impl ArtifactStatus {
+    /// Highest crit rate anything could have, in percentage.
+    pub const MAX_CRIT_RATE: u8 = 100;
+
+    /// Stack another status on top of this one, the crit rate stops at
+    /// [`ArtifactStatus::MAX_CRIT_RATE`] and the others stop at their maximum value.
+    pub fn stack(&self, other: &ArtifactStatus) -> ArtifactStatus {
+        ArtifactStatus {
+            damage: self.damage.saturating_add(other.damage),
+            health: self.health.saturating_add(other.health),
+            defence: self.defence.saturating_add(other.defence),
+            crit_rate: self
+                .crit_rate
+                .saturating_add(other.crit_rate)
+                .min(Self::MAX_CRIT_RATE),
+            crit_dmg: self.crit_dmg.saturating_add(other.crit_dmg),
+            phy_resist: self.phy_resist.saturating_add(other.phy_resist),
+            mag_resist: self.mag_resist.saturating_add(other.mag_resist),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Artifact {
+    name: String,
+    #[serde(default)]
+    description: String,
+    /// Name of the artifact set it belongs to, if any.
+    set: Option<String>,
     position: Position,
     rarity: Rarity,
-    damage: u32,
-    health: u32,
-    defence: u32,
-    crit_rate: u8,
-    crit_dmg: u16,
-    phy_resist: u8,
-    mag_resist: u8,
+    #[serde(flatten)]
+    status: ArtifactStatus,
+    value: Option<u32>,
+}
+
+impl Artifact {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn set(&self) -> Option<&str> {
+        self.set.as_deref()
+    }
+
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    pub fn rarity(&self) -> Rarity {
+        self.rarity
+    }
+
+    pub fn status(&self) -> &ArtifactStatus {
+        &self.status
+    }
+
+    pub fn damage(&self) -> u32 {
+        self.status.damage
+    }
+
+    pub fn health(&self) -> u32 {
+        self.status.health
+    }
+
+    pub fn defence(&self) -> u32 {
+        self.status.defence
+    }
+
+    pub fn crit_rate(&self) -> u8 {
+        self.status.crit_rate
+    }
+
+    pub fn crit_dmg(&self) -> u16 {
+        self.status.crit_dmg
+    }
+
+    pub fn phy_resist(&self) -> u8 {
+        self.status.phy_resist
+    }
+
+    pub fn mag_resist(&self) -> u8 {
+        self.status.mag_resist
+    }
+
+    pub fn value(&self) -> Option<u32> {
+        self.value
+    }
+}
+
+/// A set of artifacts, wearing several pieces of the same set gives extra bonuses.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ArtifactSet {
+    pub name: String,
+    /// Bonus of wearing at least 2 pieces of the set.
+    #[serde(default)]
+    pub two_piece: ArtifactStatus,
+    /// Bonus of wearing 4 pieces or more, on top of the 2-piece bonus.
+    #[serde(default)]
+    pub four_piece: ArtifactStatus,
 }
 
-#[allow(dead_code)]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Item {
     name: String,
@@ -104,7 +357,32 @@ pub struct Item {
     value: Option<u32>,
 }
 
-#[allow(dead_code)]
+impl Item {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn rarity(&self) -> Rarity {
+        self.rarity
+    }
+
+    pub fn weapon_attr(&self) -> Option<&WeaponStatus> {
+        self.weapon_attr.as_ref()
+    }
+
+    pub fn armour_attr(&self) -> Option<&ArmourStatus> {
+        self.armour_attr.as_ref()
+    }
+
+    pub fn value(&self) -> Option<u32> {
+        self.value
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Character {
     name: String,
@@ -121,3 +399,57 @@ pub struct Character {
     phy_resist: u8,
     mag_resist: u8,
 }
+
+impl Character {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn gender(&self) -> Gender {
+        self.gender
+    }
+
+    pub fn rarity(&self) -> Rarity {
+        self.rarity
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn cha_type(&self) -> ChaType {
+        self.cha_type
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn max_health(&self) -> u32 {
+        self.max_health
+    }
+
+    pub fn max_stamina(&self) -> u32 {
+        self.max_stamina
+    }
+
+    pub fn max_mana(&self) -> u32 {
+        self.max_mana
+    }
+
+    pub fn crit_rate(&self) -> u8 {
+        self.crit_rate
+    }
+
+    pub fn crit_dmg(&self) -> u16 {
+        self.crit_dmg
+    }
+
+    pub fn phy_resist(&self) -> u8 {
+        self.phy_resist
+    }
+
+    pub fn mag_resist(&self) -> u8 {
+        self.mag_resist
+    }
+}
@@ -0,0 +1,42 @@
+//! Parse, validate and look up the item data files of the games, such as `res/items.json`.
+//!
+//! Data files could be written in JSON, TOML, YAML or RON (see [`format`]), and are validated
+//! while being loaded (see [`validate`]). An [`ItemDb`] indexes the loaded entries by name,
+//! rarity and category, so pools of items don't have to be hardcoded, e.g. for a gacha banner:
+//!
+//! ```
+//! use item_parser::{ItemDb, Rarity};
+//! use std::path::Path;
+//!
+//! let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("res/items.json");
+//! let db = ItemDb::load(&path).unwrap();
+//!
+//! // one list of names for each tier of the banner
+//! let pools: Vec<(Rarity, Vec<&str>)> = Rarity::ALL
+//!     .into_iter()
+//!     .map(|rarity| (rarity, db.names(rarity, None)))
+//!     .collect();
+//! assert!(pools[4].1.contains(&"Mona"));
+//!
+//! let sword = db.get("iron sword").unwrap();
+//! assert_eq!(sword.category, "weapons");
+//! ```
+
+mod database;
+pub mod diff;
+pub mod format;
+pub mod game_data;
+pub mod loadout;
+pub mod migrate;
+pub mod query;
+pub mod validate;
+
+pub use database::ItemDb;
+pub use diff::Change;
+pub use format::Format;
+pub use game_data::{
+    ArmourStatus, Artifact, ArtifactSet, ArtifactStatus, ChaType, Character, Gender, Item, Items,
+    Position, Rarity, WeaponStatus, ARTIFACT_SETS, CATEGORIES,
+};
+pub use loadout::Loadout;
+pub use validate::{LoadError, Problem};
@@ -0,0 +1,169 @@
+use std::collections::BTreeMap;
+
+use crate::game_data::{Artifact, ArtifactSet, ArtifactStatus, Position};
+
+/// Pieces of an artifact set needed for its 2-piece bonus.
+pub const TWO_PIECE: usize = 2;
+/// Pieces of an artifact set needed for its 4-piece bonus.
+pub const FOUR_PIECE: usize = 4;
+
+/// Artifacts worn by a character, at most one in each [`Position`].
+#[derive(Clone, Debug, Default)]
+pub struct Loadout<'a> {
+    artifacts: BTreeMap<Position, &'a Artifact>,
+}
+
+impl<'a> Loadout<'a> {
+    pub fn new() -> Self {
+        Loadout::default()
+    }
+
+    /// Wear an artifact in its position, returning the one that was worn there before.
+    pub fn equip(&mut self, artifact: &'a Artifact) -> Option<&'a Artifact> {
+        self.artifacts.insert(artifact.position(), artifact)
+    }
+
+    pub fn unequip(&mut self, position: Position) -> Option<&'a Artifact> {
+        self.artifacts.remove(&position)
+    }
+
+    pub fn get(&self, position: Position) -> Option<&'a Artifact> {
+        self.artifacts.get(&position).copied()
+    }
+
+    /// Worn artifacts, by the order of their positions.
+    pub fn artifacts(&self) -> impl Iterator<Item = &'a Artifact> + '_ {
+        self.artifacts.values().copied()
+    }
+
+    /// Number of pieces worn of each artifact set.
+    pub fn set_pieces(&self) -> BTreeMap<&'a str, usize> {
+        let mut pieces = BTreeMap::new();
+        for set in self.artifacts().filter_map(Artifact::set) {
+            *pieces.entry(set).or_default() += 1;
+        }
+        pieces
+    }
+
+    /// Stats of every worn artifact stacked together, plus the bonus of each set worn
+    /// with enough pieces. Sets missing from `sets` give no bonus.
+    pub fn total(&self, sets: &[ArtifactSet]) -> ArtifactStatus {
+        let pieces = self.artifacts().map(Artifact::status);
+        let bonuses = self.set_pieces().into_iter().flat_map(|(name, count)| {
+            let set = sets.iter().find(|set| set.name == name);
+            let two_piece = set.filter(|_| count >= TWO_PIECE).map(|s| &s.two_piece);
+            let four_piece = set.filter(|_| count >= FOUR_PIECE).map(|s| &s.four_piece);
+            two_piece.into_iter().chain(four_piece)
+        });
+        pieces
+            .chain(bonuses)
+            .fold(ArtifactStatus::default(), |total, status| {
+                total.stack(status)
+            })
+    }
+}
+
+#[cfg(test)]
+mod loadout_tests {
+    use super::*;
+    use crate::database::ItemDb;
+    use crate::query::Entry;
+    use std::path::Path;
+
+    fn db() -> ItemDb {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("res/items.json");
+        ItemDb::load(&path).unwrap()
+    }
+
+    fn artifact<'a>(db: &'a ItemDb, name: &str) -> &'a Artifact {
+        match db.get(name).unwrap().entry {
+            Entry::Artifact(artifact) => artifact,
+            _ => panic!("{} should be an artifact", name),
+        }
+    }
+
+    #[test]
+    fn one_artifact_per_position() {
+        let db = db();
+        let mut loadout = Loadout::new();
+        let helm = artifact(&db, "Berserker's Helm");
+        assert!(loadout.equip(helm).is_none());
+        let circlet = artifact(&db, "Guardian's Circlet");
+        assert_eq!(circlet.position(), Position::Head);
+        assert_eq!(loadout.equip(circlet).unwrap().name(), "Berserker's Helm");
+        assert_eq!(
+            loadout.get(Position::Head).unwrap().name(),
+            "Guardian's Circlet"
+        );
+        assert_eq!(loadout.artifacts().count(), 1);
+        assert!(loadout.unequip(Position::Head).is_some());
+        assert!(loadout.get(Position::Head).is_none());
+    }
+
+    #[test]
+    fn set_bonuses() {
+        let db = db();
+        let sets = db.items().artifact_sets();
+        let mut loadout = Loadout::new();
+        assert_eq!(loadout.total(sets), ArtifactStatus::default());
+
+        loadout.equip(artifact(&db, "Berserker's Helm"));
+        let one_piece = loadout.total(sets);
+        assert_eq!((one_piece.damage, one_piece.crit_rate), (10, 5));
+
+        loadout.equip(artifact(&db, "Berserker's Armour"));
+        let two_piece = loadout.total(sets);
+        // 10 + 15 from the pieces, 20 from the 2-piece bonus
+        assert_eq!((two_piece.damage, two_piece.crit_rate), (45, 10));
+
+        loadout.equip(artifact(&db, "Berserker's Greaves"));
+        loadout.equip(artifact(&db, "Berserker's Boots"));
+        loadout.equip(artifact(&db, "Wanderer's Pendant"));
+        assert_eq!(
+            loadout.set_pieces().into_iter().collect::<Vec<_>>(),
+            vec![("Berserker", 4)]
+        );
+        let four_piece = loadout.total(sets);
+        assert_eq!(four_piece.damage, 10 + 15 + 10 + 5 + 20 + 40);
+        assert_eq!(four_piece.crit_rate, 5 + 5 + 5 + 5 + 20);
+        assert_eq!(four_piece.health, 300);
+
+        // a single piece of another set gives nothing more than its own stats
+        loadout.equip(artifact(&db, "Guardian's Circlet"));
+        let mixed = loadout.total(sets);
+        // and three pieces of a set still give its 2-piece bonus
+        assert_eq!(mixed.damage, four_piece.damage - 10 - 40);
+        assert_eq!(mixed.defence, 30);
+    }
+
+    #[test]
+    fn crit_rate_is_capped() {
+        let db = db();
+        let mut loadout = Loadout::new();
+        for name in [
+            "Berserker's Helm",
+            "Berserker's Armour",
+            "Berserker's Greaves",
+            "Berserker's Boots",
+        ] {
+            loadout.equip(artifact(&db, name));
+        }
+        let sets = [ArtifactSet {
+            name: "Berserker".to_string(),
+            two_piece: ArtifactStatus {
+                crit_rate: 50,
+                ..ArtifactStatus::default()
+            },
+            four_piece: ArtifactStatus {
+                crit_rate: 50,
+                ..ArtifactStatus::default()
+            },
+        }];
+        assert_eq!(
+            loadout.total(&sets).crit_rate,
+            ArtifactStatus::MAX_CRIT_RATE
+        );
+        // without the definition of the set, only the pieces count
+        assert_eq!(loadout.total(&[]).crit_rate, 20);
+    }
+}
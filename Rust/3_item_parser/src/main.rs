@@ -1,54 +1,369 @@
-mod game_data;
+use clap::builder::PossibleValuesParser;
+use clap::{value_parser, Arg, ArgMatches, Command};
+use item_parser::query::{self, Listed, Stats};
+use item_parser::{diff, format, validate, Change, Format, ItemDb, Rarity, CATEGORIES};
+use serde::Serialize;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process;
 
-use game_data::Items;
-use serde_json::from_str;
+const DEFAULT_ITEM_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/res/items.json");
 
-fn main() {
-    let item_json_raw = include_str!("../res/items.json");
-    //println!("Item Json: {}", item_json_raw);
+fn cli() -> Command {
+    let format_arg = |name: &'static str, help: &'static str| {
+        Arg::new(name)
+            .long(name)
+            .value_name("FORMAT")
+            .help(help)
+            .value_parser(|s: &str| s.parse::<Format>())
+    };
 
-    let item_struct: Items = from_str(item_json_raw).unwrap();
-    println!("Item Struct:\n{:#?}", item_struct);
+    Command::new(env!("CARGO_PKG_NAME"))
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Parse, validate and query item data files")
+        .arg(
+            Arg::new("file")
+                .long("file")
+                .short('f')
+                .value_name("PATH")
+                .help("Path to the data file, in JSON, TOML, YAML or RON")
+                .global(true)
+                .value_parser(value_parser!(PathBuf))
+                .default_value(DEFAULT_ITEM_FILE),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .value_name("FORMAT")
+                .help("Print the results as a table or as JSON")
+                .global(true)
+                .value_parser(["table", "json"])
+                .default_value("table"),
+        )
+        .subcommand(
+            Command::new("check")
+                .about("Validate the data file and count what is in it (the default)"),
+        )
+        .subcommand(
+            Command::new("list")
+                .about("List the items and characters")
+                .arg(
+                    Arg::new("category")
+                        .long("category")
+                        .short('c')
+                        .value_name("CATEGORY")
+                        .help("Only list the entries of a category")
+                        .value_parser(PossibleValuesParser::new(CATEGORIES)),
+                )
+                .arg(
+                    Arg::new("rarity")
+                        .long("rarity")
+                        .short('r')
+                        .value_name("RARITY")
+                        .help("Only list the entries of a rarity")
+                        .value_parser(|s: &str| s.parse::<Rarity>()),
+                ),
+        )
+        .subcommand(
+            Command::new("show")
+                .about("Show everything about an item or a character")
+                .arg(
+                    Arg::new("name")
+                        .value_name("NAME")
+                        .help("Name of the item or character, case insensitive")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("stats")
+                .about("Count the entries, and summarize the damage and value of the items"),
+        )
+        .subcommand(
+            Command::new("diff")
+                .about(
+                    "Show what changed between two data files, entry by entry and field by field",
+                )
+                .arg(
+                    Arg::new("old")
+                        .value_name("OLD")
+                        .help("Path to the data file before the changes")
+                        .value_parser(value_parser!(PathBuf))
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("new")
+                        .value_name("NEW")
+                        .help("Path to the data file after the changes")
+                        .value_parser(value_parser!(PathBuf))
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("convert")
+                .about("Translate a data file to another format")
+                .arg(
+                    Arg::new("input")
+                        .value_name("INPUT")
+                        .help("Path to the file to convert")
+                        .value_parser(value_parser!(PathBuf))
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("dest")
+                        .value_name("OUTPUT")
+                        .help("Path to write the converted file to, `-` for stdout")
+                        .value_parser(value_parser!(PathBuf))
+                        .required(true),
+                )
+                .arg(format_arg(
+                    "from",
+                    "Format of the input, guessed from its extension by default",
+                ))
+                .arg(format_arg(
+                    "to",
+                    "Format of the output, guessed from its extension by default",
+                )),
+        )
+}
 
-    let num_armor = if item_struct.armours.is_none() {
-        0
-    } else {
-        item_struct.armours.unwrap().len()
-    };
-    let num_artfs = if item_struct.artifacts.is_none() {
-        0
-    } else {
-        item_struct.artifacts.unwrap().len()
-    };
-    let num_chars = if item_struct.characters.is_none() {
-        0
-    } else {
-        item_struct.characters.unwrap().len()
-    };
-    let num_consm = if item_struct.consumable.is_none() {
-        0
-    } else {
-        item_struct.consumable.unwrap().len()
-    };
-    let num_mats = if item_struct.materials.is_none() {
-        0
-    } else {
-        item_struct.materials.unwrap().len()
+fn main() {
+    let matches = cli().get_matches();
+    let result = match matches.subcommand() {
+        Some(("convert", sub_matches)) => convert(sub_matches),
+        Some(("diff", sub_matches)) => compare(sub_matches),
+        Some((name, sub_matches)) => query(name, sub_matches),
+        None => query("check", &matches),
     };
-    let num_tra = if item_struct.tradable.is_none() {
-        0
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+/// Run a command that reads the data file.
+fn query(command: &str, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let path = matches.get_one::<PathBuf>("file").unwrap();
+    let json = matches.get_one::<String>("output").unwrap() == "json";
+    let db = ItemDb::load(path)?;
+    let items = db.items();
+
+    match command {
+        "list" => {
+            let listed = query::list(
+                items,
+                matches.get_one::<String>("category").map(String::as_str),
+                matches.get_one::<Rarity>("rarity").copied(),
+            );
+            if json {
+                print_json(&listed)?;
+            } else {
+                print_list(&listed);
+            }
+        }
+        "show" => {
+            let name = matches.get_one::<String>("name").unwrap();
+            let found = db
+                .get(name)
+                .ok_or_else(|| format!("no entry named `{}`", name))?;
+            if json {
+                print_json(&found)?;
+            } else {
+                print_entry(&found)?;
+            }
+        }
+        "stats" => {
+            let stats = query::stats(items);
+            if json {
+                print_json(&stats)?;
+            } else {
+                print_stats(&stats);
+            }
+        }
+        _ => {
+            let counts = query::stats(items).counts;
+            if json {
+                print_json(&counts)?;
+            } else {
+                println!("{} is valid", path.display());
+                print_table(
+                    &["Category", "Count"],
+                    counts
+                        .iter()
+                        .map(|(c, n)| vec![c.to_string(), n.to_string()]),
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), Box<dyn Error>> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// Print rows in columns, each column as wide as its widest cell.
+fn print_table<I: IntoIterator<Item = Vec<String>>>(headers: &[&str], rows: I) {
+    let rows: Vec<Vec<String>> = rows.into_iter().collect();
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let headers: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+    let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+    for row in [&headers, &rule].into_iter().chain(&rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<1$}", cell, width))
+            .collect();
+        println!("{}", cells.join("  ").trim_end());
+    }
+}
+
+fn print_list(listed: &[Listed]) {
+    print_table(
+        &["Name", "Category", "Rarity", "Value"],
+        listed.iter().map(|l| {
+            vec![
+                l.entry.name().to_string(),
+                l.category.to_string(),
+                l.entry.rarity().to_string(),
+                l.entry.value().map(|v| v.to_string()).unwrap_or_default(),
+            ]
+        }),
+    );
+}
+
+/// Print every field of an entry, nested fields are written as `parent.field`.
+fn print_entry(listed: &Listed) -> Result<(), Box<dyn Error>> {
+    fn flatten(prefix: String, value: serde_json::Value, rows: &mut Vec<Vec<String>>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, value) in map {
+                    let key = if prefix.is_empty() {
+                        key
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    flatten(key, value, rows);
+                }
+            }
+            serde_json::Value::Null => (),
+            serde_json::Value::String(s) => rows.push(vec![prefix, s]),
+            value => rows.push(vec![prefix, value.to_string()]),
+        }
+    }
+
+    let mut rows = vec![];
+    flatten(String::new(), serde_json::to_value(listed)?, &mut rows);
+    print_table(&["Field", "Value"], rows);
+    Ok(())
+}
+
+fn print_stats(stats: &Stats) {
+    print_table(
+        &["Category", "Count"],
+        stats
+            .counts
+            .iter()
+            .map(|(c, n)| vec![c.to_string(), n.to_string()]),
+    );
+    println!();
+    print_table(
+        &["Rarity", "Weapons", "Average damage"],
+        stats.damage.iter().map(|d| {
+            vec![
+                d.rarity.to_string(),
+                d.weapons.to_string(),
+                format!("{:.1}", d.average),
+            ]
+        }),
+    );
+    println!();
+    print_table(
+        &["Value", "Items"],
+        stats
+            .values
+            .distribution
+            .iter()
+            .map(|(range, n)| vec![range.clone(), n.to_string()]),
+    );
+    if let (Some(min), Some(max), Some(average)) =
+        (stats.values.min, stats.values.max, stats.values.average)
+    {
+        println!(
+            "{} items with a value, from {} to {}, {:.1} on average",
+            stats.values.count, min, max, average
+        );
+    }
+}
+
+fn compare(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let old = validate::load(matches.get_one::<PathBuf>("old").unwrap())?;
+    let new = validate::load(matches.get_one::<PathBuf>("new").unwrap())?;
+    let changes: Vec<Change> = diff::diff(&old, &new);
+    if matches.get_one::<String>("output").unwrap() == "json" {
+        print_json(&changes)?;
+    } else if changes.is_empty() {
+        println!("no changes");
     } else {
-        item_struct.tradable.unwrap().len()
+        for change in &changes {
+            println!("{}", change);
+        }
+    }
+    Ok(())
+}
+
+fn convert(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let input = matches.get_one::<PathBuf>("input").unwrap();
+    let output = matches.get_one::<PathBuf>("dest").unwrap();
+    let format_of = |name: &str, path: &Path| {
+        matches
+            .get_one::<Format>(name)
+            .copied()
+            .or_else(|| Format::from_path(path))
+            .ok_or_else(|| {
+                format!(
+                    "cannot tell the format of {}, use --{} to set it",
+                    path.display(),
+                    name
+                )
+            })
     };
-    let num_weap = if item_struct.weapons.is_none() {
-        0
+    let from = format_of("from", input)?;
+    let to = format_of("to", output)?;
+
+    let src = std::fs::read_to_string(input)
+        .map_err(|e| format!("cannot read {}: {}", input.display(), e))?;
+    let converted = format::convert(&src, from, to)
+        .map_err(|e| format!("cannot convert {} to {}: {}", input.display(), to, e))?;
+    if output == Path::new("-") {
+        print!("{}", converted);
     } else {
-        item_struct.weapons.unwrap().len()
-    };
+        std::fs::write(output, converted)
+            .map_err(|e| format!("cannot write {}: {}", output.display(), e))?;
+    }
+    Ok(())
+}
 
-    println!(
-        "There are {0} Armour pieces, {1} Artifacts, {2} Characters, {3} Consumables, 
-    {4} Materials, {5} Tradables, {6} Weapons in the Item data file. ",
-        num_armor, num_artfs, num_chars, num_consm, num_mats, num_tra, num_weap
-    );
+#[cfg(test)]
+mod main_tests {
+    use super::*;
+
+    #[test]
+    fn convert_keeps_the_output_flag() {
+        let matches = cli()
+            .try_get_matches_from(["item_parser", "convert", "a.json", "b.ron", "-o", "json"])
+            .unwrap();
+        assert_eq!(matches.get_one::<String>("output").unwrap(), "json");
+        let (_, convert) = matches.subcommand().unwrap();
+        assert_eq!(
+            convert.get_one::<PathBuf>("dest").unwrap(),
+            Path::new("b.ron")
+        );
+    }
 }
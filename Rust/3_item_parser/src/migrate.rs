@@ -0,0 +1,215 @@
+//! Versions of the data file schema, and how to upgrade a file from one to the next.
+//!
+//! A data file tells its version with a top-level `version` key, files without one are
+//! version 1. Loading a file of an older version runs every migration since then, so old
+//! files keep loading after a category or a field is renamed.
+
+use serde_json::{Map, Value};
+
+/// Key of the data file holding the version of its schema.
+pub const VERSION: &str = "version";
+
+/// Version of the schema the types of [`crate::game_data`] are written for.
+pub const CURRENT_VERSION: u64 = 3;
+
+/// Upgrades a file from a version to the next one, returning every value it moved,
+/// as JSON pointers from where it was to where it is now.
+type Migration = fn(&mut Map<String, Value>) -> Vec<(String, String)>;
+
+/// Migrations of each version to the next, the first one upgrades version 1.
+const MIGRATIONS: [Migration; (CURRENT_VERSION - 1) as usize] = [consumables, artifacts];
+
+/// Version 2 renamed the `consumable` category to `consumables`.
+fn consumables(map: &mut Map<String, Value>) -> Vec<(String, String)> {
+    rename(map, "consumable", "consumables")
+        .into_iter()
+        .collect()
+}
+
+/// Stats of an artifact, the ones it keeps from the attributes of an item.
+const ARTIFACT_STATS: [&str; 7] = [
+    "damage",
+    "health",
+    "defence",
+    "crit_rate",
+    "crit_dmg",
+    "phy_resist",
+    "mag_resist",
+];
+
+/// Version 3 gave artifacts their own fields instead of the ones of an item. The stats of their
+/// `weapon_attr` and `armour_attr` move up into the artifact, the weapon's first when both have
+/// one, and the other attributes are dropped. An artifact without a `position` is worn as a
+/// weapon if it had a `weapon_attr`, or on the chest otherwise.
+fn artifacts(map: &mut Map<String, Value>) -> Vec<(String, String)> {
+    let mut moved = vec![];
+    let artifacts = match map.get_mut("artifacts") {
+        Some(Value::Array(artifacts)) => artifacts,
+        _ => return moved,
+    };
+    for (i, artifact) in artifacts.iter_mut().enumerate() {
+        let artifact = match artifact {
+            Value::Object(artifact) if !artifact.contains_key("position") => artifact,
+            _ => continue,
+        };
+        let position = if artifact.get("weapon_attr").is_some_and(Value::is_object) {
+            "Weapon"
+        } else {
+            "Chest"
+        };
+        for attr in ["weapon_attr", "armour_attr"] {
+            let stats = match artifact.remove(attr) {
+                Some(Value::Object(stats)) => stats,
+                _ => continue,
+            };
+            for (stat, value) in stats {
+                if ARTIFACT_STATS.contains(&stat.as_str()) && !artifact.contains_key(&stat) {
+                    moved.push((
+                        format!("/artifacts/{}/{}/{}", i, attr, stat),
+                        format!("/artifacts/{}/{}", i, stat),
+                    ));
+                    artifact.insert(stat, value);
+                }
+            }
+        }
+        artifact.insert("position".to_string(), position.into());
+    }
+    moved
+}
+
+/// Rename a top-level key, unless the new name is already used.
+fn rename(map: &mut Map<String, Value>, from: &str, to: &str) -> Option<(String, String)> {
+    if map.contains_key(to) {
+        return None;
+    }
+    let value = map.remove(from)?;
+    map.insert(to.to_string(), value);
+    Some((format!("/{}", from), format!("/{}", to)))
+}
+
+/// Upgrade the content of a data file to [`CURRENT_VERSION`], in place.
+///
+/// Returns the pointers of the values that were moved, from where they were to where they are
+/// now, or why the version is wrong. Anything that is not an object is left as is.
+pub fn migrate(value: &mut Value) -> Result<Vec<(String, String)>, String> {
+    let map = match value {
+        Value::Object(map) => map,
+        _ => return Ok(vec![]),
+    };
+    let version = match map.get(VERSION) {
+        None => 1,
+        Some(version) => match version.as_u64() {
+            Some(v) if (1..=CURRENT_VERSION).contains(&v) => v,
+            Some(v) if v > CURRENT_VERSION => {
+                return Err(format!(
+                    "version {} is newer than the latest supported version {}",
+                    v, CURRENT_VERSION
+                ))
+            }
+            _ => {
+                return Err(format!(
+                    "expected a version from 1 to {}, got {}",
+                    CURRENT_VERSION, version
+                ))
+            }
+        },
+    };
+
+    let mut moved = vec![];
+    for migration in &MIGRATIONS[version as usize - 1..] {
+        moved.extend(migration(map));
+    }
+    map.insert(VERSION.to_string(), CURRENT_VERSION.into());
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod migrate_tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn upgrade_version_1() {
+        let mut value = json!({ "consumable": [], "weapons": [] });
+        let moved = migrate(&mut value).unwrap();
+        assert_eq!(
+            value,
+            json!({ "version": CURRENT_VERSION, "consumables": [], "weapons": [] })
+        );
+        assert_eq!(
+            moved,
+            vec![("/consumable".to_string(), "/consumables".to_string())]
+        );
+
+        // already renamed, the old key is kept to be reported as unknown
+        let mut value = json!({ "consumable": [], "consumables": [] });
+        assert!(migrate(&mut value).unwrap().is_empty());
+        assert!(value.get("consumable").is_some());
+    }
+
+    #[test]
+    fn upgrade_version_2() {
+        let mut value = json!({
+            "version": 2,
+            "artifacts": [
+                {
+                    "name": "Old Ring",
+                    "rarity": "Rare",
+                    "weapon_attr": { "level": 3, "damage": 10, "crit_rate": 5 },
+                    "armour_attr": { "damage": 99, "defence": 4 }
+                },
+                { "name": "Old Charm", "rarity": "Normal" },
+                { "name": "New Helm", "position": "Head", "rarity": "Rare", "damage": 2 }
+            ]
+        });
+        let moved = migrate(&mut value).unwrap();
+        assert_eq!(
+            value["artifacts"],
+            json!([
+                {
+                    "name": "Old Ring",
+                    "rarity": "Rare",
+                    "position": "Weapon",
+                    "damage": 10,
+                    "crit_rate": 5,
+                    "defence": 4
+                },
+                { "name": "Old Charm", "rarity": "Normal", "position": "Chest" },
+                { "name": "New Helm", "position": "Head", "rarity": "Rare", "damage": 2 }
+            ])
+        );
+        assert_eq!(
+            moved,
+            [
+                (
+                    "/artifacts/0/weapon_attr/crit_rate",
+                    "/artifacts/0/crit_rate"
+                ),
+                ("/artifacts/0/weapon_attr/damage", "/artifacts/0/damage"),
+                ("/artifacts/0/armour_attr/defence", "/artifacts/0/defence"),
+            ]
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+        );
+    }
+
+    #[test]
+    fn current_version_is_untouched() {
+        let mut value = json!({ "version": 3, "consumables": [] });
+        let before = value.clone();
+        assert!(migrate(&mut value).unwrap().is_empty());
+        assert_eq!(value, before);
+    }
+
+    #[test]
+    fn wrong_version() {
+        assert_eq!(
+            migrate(&mut json!({ "version": 4 })).unwrap_err(),
+            "version 4 is newer than the latest supported version 3"
+        );
+        assert_eq!(
+            migrate(&mut json!({ "version": "two" })).unwrap_err(),
+            "expected a version from 1 to 3, got \"two\""
+        );
+        assert!(migrate(&mut json!({ "version": 0 })).is_err());
+    }
+}
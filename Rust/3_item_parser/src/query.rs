@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::game_data::{Artifact, Character, Item, Items, Rarity, CATEGORIES};
+
+/// Upper bounds (exclusive) of the value ranges in [`ValueStats::distribution`],
+/// the last range has no upper bound.
+const VALUE_BOUNDS: [u32; 3] = [100, 1000, 10000];
+
+/// An item, a character or an artifact of the data file.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum Entry<'a> {
+    Item(&'a Item),
+    Character(&'a Character),
+    Artifact(&'a Artifact),
+}
+
+impl<'a> Entry<'a> {
+    pub fn name(&self) -> &'a str {
+        match self {
+            Entry::Item(item) => item.name(),
+            Entry::Character(cha) => cha.name(),
+            Entry::Artifact(artifact) => artifact.name(),
+        }
+    }
+
+    pub fn rarity(&self) -> Rarity {
+        match self {
+            Entry::Item(item) => item.rarity(),
+            Entry::Character(cha) => cha.rarity(),
+            Entry::Artifact(artifact) => artifact.rarity(),
+        }
+    }
+
+    pub fn value(&self) -> Option<u32> {
+        match self {
+            Entry::Item(item) => item.value(),
+            Entry::Artifact(artifact) => artifact.value(),
+            Entry::Character(_) => None,
+        }
+    }
+}
+
+/// An entry along with the category it was found in.
+#[derive(Debug, Serialize)]
+pub struct Listed<'a> {
+    pub category: &'static str,
+    #[serde(flatten)]
+    pub entry: Entry<'a>,
+}
+
+/// Entries of a category, `None` if there is no such category.
+pub fn category<'a>(items: &'a Items, category: &str) -> Option<Vec<Listed<'a>>> {
+    // to get the `'static` name of the category
+    let category = *CATEGORIES.iter().find(|c| **c == category)?;
+    let entries: Vec<Entry> = match (category, items.category(category)) {
+        (_, Some(list)) => list.iter().map(Entry::Item).collect(),
+        ("artifacts", None) => items.artifacts().iter().map(Entry::Artifact).collect(),
+        _ => items.characters().iter().map(Entry::Character).collect(),
+    };
+    let listed = entries
+        .into_iter()
+        .map(|entry| Listed { category, entry })
+        .collect();
+    Some(listed)
+}
+
+/// Every entry of the data file, by the order of their categories then the order in the file.
+pub fn entries(items: &Items) -> Vec<Listed<'_>> {
+    CATEGORIES
+        .iter()
+        .flat_map(|c| category(items, c).unwrap_or_default())
+        .collect()
+}
+
+/// Entries of a category and a rarity, `None` means any.
+pub fn list<'a>(
+    items: &'a Items,
+    category: Option<&str>,
+    rarity: Option<Rarity>,
+) -> Vec<Listed<'a>> {
+    entries(items)
+        .into_iter()
+        .filter(|l| category.is_none_or(|c| c == l.category))
+        .filter(|l| rarity.is_none_or(|r| r == l.entry.rarity()))
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Stats {
+    /// Number of entries in each category.
+    pub counts: BTreeMap<&'static str, usize>,
+    /// Damage of the weapons of each rarity, only the rarities that have weapons.
+    pub damage: Vec<DamageStats>,
+    /// Values of every item that has one.
+    pub values: ValueStats,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DamageStats {
+    pub rarity: Rarity,
+    pub weapons: usize,
+    pub average: f64,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ValueStats {
+    pub count: usize,
+    pub min: Option<u32>,
+    pub max: Option<u32>,
+    pub average: Option<f64>,
+    /// Number of items in each value range, e.g. `100-999`.
+    pub distribution: Vec<(String, usize)>,
+}
+
+pub fn stats(items: &Items) -> Stats {
+    let entries = entries(items);
+
+    let mut counts = BTreeMap::new();
+    for listed in &entries {
+        *counts.entry(listed.category).or_default() += 1;
+    }
+
+    let damage = Rarity::ALL
+        .into_iter()
+        .filter_map(|rarity| {
+            let damages: Vec<u32> = items
+                .weapons
+                .iter()
+                .flatten()
+                .filter(|w| w.rarity() == rarity)
+                .filter_map(|w| w.weapon_attr().map(|attr| attr.damage()))
+                .collect();
+            if damages.is_empty() {
+                return None;
+            }
+            Some(DamageStats {
+                rarity,
+                weapons: damages.len(),
+                average: average(&damages),
+            })
+        })
+        .collect();
+
+    let values: Vec<u32> = entries.iter().filter_map(|l| l.entry.value()).collect();
+    let mut distribution: Vec<(String, usize)> = vec![];
+    let mut lower = 0;
+    for upper in VALUE_BOUNDS {
+        let count = values
+            .iter()
+            .filter(|v| (lower..upper).contains(*v))
+            .count();
+        distribution.push((format!("{}-{}", lower, upper - 1), count));
+        lower = upper;
+    }
+    let count = values.iter().filter(|v| **v >= lower).count();
+    distribution.push((format!("{}+", lower), count));
+
+    Stats {
+        counts,
+        damage,
+        values: ValueStats {
+            count: values.len(),
+            min: values.iter().min().copied(),
+            max: values.iter().max().copied(),
+            average: (!values.is_empty()).then(|| average(&values)),
+            distribution,
+        },
+    }
+}
+
+fn average(values: &[u32]) -> f64 {
+    values.iter().map(|v| f64::from(*v)).sum::<f64>() / values.len() as f64
+}
+
+#[cfg(test)]
+mod query_tests {
+    use super::*;
+    use crate::format::Format;
+    use std::path::Path;
+
+    fn items() -> Items {
+        let src = r#"{
+            "weapons": [
+                { "name": "Iron Sword", "description": "", "rarity": "Normal", "value": 100,
+                  "weapon_attr": { "level": 0, "damage": 10, "ex_damage": 0, "ex_duration": 0, "crit_rate": 0, "crit_dmg": 0 } },
+                { "name": "Wooden Sword", "description": "", "rarity": "Normal", "value": 20,
+                  "weapon_attr": { "level": 0, "damage": 5, "ex_damage": 0, "ex_duration": 0, "crit_rate": 0, "crit_dmg": 0 } },
+                { "name": "Fire Sword", "description": "", "rarity": "Epic", "value": 3000,
+                  "weapon_attr": { "level": 0, "damage": 110, "ex_damage": 0, "ex_duration": 0, "crit_rate": 0, "crit_dmg": 0 } }
+            ],
+            "materials": [
+                { "name": "Dragon Scale", "description": "", "rarity": "Epic", "value": 12000 },
+                { "name": "Pebble", "description": "", "rarity": "Normal" }
+            ],
+            "characters": [
+                { "name": "Mona", "gender": "Female", "description": "", "rarity": "Legendary",
+                  "cha_type": "Mage", "level": 0, "max_health": 1, "max_stamina": 1, "max_mana": 1,
+                  "crit_rate": 10, "crit_dmg": 120, "phy_resist": 10, "mag_resist": 25 }
+            ]
+        }"#;
+        crate::validate::parse(Path::new("items.json"), src, Format::Json).unwrap()
+    }
+
+    fn names(listed: Vec<Listed>) -> Vec<String> {
+        listed.iter().map(|l| l.entry.name().to_string()).collect()
+    }
+
+    #[test]
+    fn list_entries() {
+        let items = items();
+        assert_eq!(
+            names(list(&items, None, None)),
+            vec![
+                "Mona",
+                "Dragon Scale",
+                "Pebble",
+                "Iron Sword",
+                "Wooden Sword",
+                "Fire Sword"
+            ]
+        );
+        assert_eq!(
+            names(list(&items, Some("weapons"), Some(Rarity::Normal))),
+            vec!["Iron Sword", "Wooden Sword"]
+        );
+        assert_eq!(
+            names(list(&items, None, Some(Rarity::Epic))),
+            vec!["Dragon Scale", "Fire Sword"]
+        );
+        assert!(list(&items, Some("armours"), None).is_empty());
+    }
+
+    #[test]
+    fn compute_stats() {
+        let stats = stats(&items());
+        assert_eq!(
+            stats.counts.into_iter().collect::<Vec<_>>(),
+            vec![("characters", 1), ("materials", 2), ("weapons", 3)]
+        );
+        assert_eq!(
+            stats.damage,
+            vec![
+                DamageStats {
+                    rarity: Rarity::Normal,
+                    weapons: 2,
+                    average: 7.5
+                },
+                DamageStats {
+                    rarity: Rarity::Epic,
+                    weapons: 1,
+                    average: 110.0
+                },
+            ]
+        );
+        assert_eq!(stats.values.count, 4);
+        assert_eq!(
+            (stats.values.min, stats.values.max),
+            (Some(20), Some(12000))
+        );
+        assert_eq!(stats.values.average, Some(3780.0));
+        assert_eq!(
+            stats.values.distribution,
+            vec![
+                ("0-99".to_string(), 1),
+                ("100-999".to_string(), 1),
+                ("1000-9999".to_string(), 1),
+                ("10000+".to_string(), 1),
+            ]
+        );
+    }
+}
@@ -0,0 +1,582 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::format::Format;
+use crate::game_data::{Artifact, ArtifactSet, Character, Item, Items, ARTIFACT_SETS, CATEGORIES};
+use crate::migrate::{self, VERSION};
+
+/// Highest `crit_rate` anything could have, in percentage.
+const MAX_CRIT_RATE: u64 = 100;
+
+/// Something wrong in a data file, with where it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub file: PathBuf,
+    /// JSON pointer to the value with the problem, empty for the whole document.
+    pub pointer: String,
+    /// Line and column of the value, both starting from 1.
+    /// Only known for JSON files, or for syntax errors that tell where they are.
+    pub location: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:", self.file.display())?;
+        if let Some((line, column)) = self.location {
+            write!(f, "{}:{}:", line, column)?;
+        }
+        f.write_str(" ")?;
+        if !self.pointer.is_empty() {
+            write!(f, "{}: ", self.pointer)?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file could not be read at all.
+    Io(PathBuf, io::Error),
+    /// The format of the file could not be told from its extension.
+    UnknownFormat(PathBuf),
+    /// Every problem found in the file, in the order they appear.
+    Invalid(Vec<Problem>),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Io(file, e) => write!(f, "cannot read {}: {}", file.display(), e),
+            LoadError::UnknownFormat(file) => write!(
+                f,
+                "cannot tell the format of {}, expected a .json, .toml, .yaml or .ron file",
+                file.display()
+            ),
+            LoadError::Invalid(problems) => {
+                for (i, problem) in problems.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{}", problem)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// Read and validate a data file, in a format told by its extension.
+pub fn load(path: &Path) -> Result<Items, LoadError> {
+    let format =
+        Format::from_path(path).ok_or_else(|| LoadError::UnknownFormat(path.to_path_buf()))?;
+    let src = std::fs::read_to_string(path).map_err(|e| LoadError::Io(path.to_path_buf(), e))?;
+    parse(path, &src, format).map_err(LoadError::Invalid)
+}
+
+/// Parse the content of a data file, `file` is only used to tell where the problems are.
+///
+/// Files of an older version are upgraded first, see [`migrate`]. Besides what the types of [`Items`] require, every `crit_rate` has to be no more than 100,
+/// every weapon needs a `weapon_attr`, and no two entries could share a name.
+pub fn parse(file: &Path, src: &str, format: Format) -> Result<Items, Vec<Problem>> {
+    let syntax_error = |location, message| {
+        vec![Problem {
+            file: file.to_path_buf(),
+            pointer: String::new(),
+            location,
+            message,
+        }]
+    };
+    let (mut value, mut positions): (Value, _) = if format == Format::Json {
+        let value = serde_json::from_str(src)
+            .map_err(|e| syntax_error(Some((e.line(), e.column())), e.to_string()))?;
+        (value, locate(src))
+    } else {
+        let value = format
+            .deserialize(src)
+            .map_err(|message| syntax_error(None, message))?;
+        (value, HashMap::new())
+    };
+
+    match migrate::migrate(&mut value) {
+        // keep telling where the moved values were in the file
+        Ok(moved) => {
+            for (from, to) in moved {
+                let moved: Vec<_> = positions
+                    .keys()
+                    .filter(|p| {
+                        p.strip_prefix(&from)
+                            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+                    })
+                    .cloned()
+                    .collect();
+                for pointer in moved {
+                    let position = positions.remove(&pointer).unwrap();
+                    positions.insert(format!("{}{}", to, &pointer[from.len()..]), position);
+                }
+            }
+        }
+        Err(message) => {
+            let pointer = format!("/{}", VERSION);
+            return Err(vec![Problem {
+                file: file.to_path_buf(),
+                location: positions.get(&pointer).copied(),
+                pointer,
+                message,
+            }]);
+        }
+    }
+
+    let mut checker = Checker {
+        file,
+        positions,
+        problems: vec![],
+    };
+    checker.check(&value);
+    if checker.problems.is_empty() {
+        // should not fail after the checks, but just in case
+        match Items::deserialize(&value) {
+            Ok(items) => return Ok(items),
+            Err(e) => checker.report("", e.to_string()),
+        }
+    }
+    checker.problems.sort_by_key(|p| p.location);
+    Err(checker.problems)
+}
+
+struct Checker<'a> {
+    file: &'a Path,
+    /// Line and column of every value, by their JSON pointers, empty if they are unknown.
+    positions: HashMap<String, (usize, usize)>,
+    problems: Vec<Problem>,
+}
+
+impl Checker<'_> {
+    fn report(&mut self, pointer: &str, message: String) {
+        self.problems.push(Problem {
+            file: self.file.to_path_buf(),
+            pointer: pointer.to_string(),
+            location: self.positions.get(pointer).copied(),
+            message,
+        });
+    }
+
+    fn check(&mut self, value: &Value) {
+        let categories = match value {
+            Value::Object(map) => map,
+            _ => return self.report("", "expected an object of item lists".to_string()),
+        };
+
+        // pointers to the name of every entry, by name
+        let mut names: HashMap<&str, Vec<String>> = HashMap::new();
+        // pointers to the name of every artifact set, by name
+        let mut set_names: HashMap<&str, Vec<String>> = HashMap::new();
+        // pointer to the `set` of every artifact, with the name of the set
+        let mut set_refs: Vec<(String, &str)> = vec![];
+        for (category, list) in categories {
+            if category == VERSION {
+                continue;
+            }
+            let pointer = format!("/{}", escape(category));
+            if category != ARTIFACT_SETS && !CATEGORIES.contains(&category.as_str()) {
+                self.report(
+                    &pointer,
+                    format!(
+                        "unknown category `{}`, expected one of: {}, {}",
+                        category,
+                        CATEGORIES.join(", "),
+                        ARTIFACT_SETS
+                    ),
+                );
+                continue;
+            }
+            let entries = match list {
+                Value::Array(entries) => entries,
+                Value::Null => continue,
+                _ => {
+                    self.report(&pointer, "expected a list".to_string());
+                    continue;
+                }
+            };
+
+            for (i, entry) in entries.iter().enumerate() {
+                let pointer = format!("{}/{}", pointer, i);
+                let parsed = match category.as_str() {
+                    "characters" => Character::deserialize(entry).map(|_| ()),
+                    "artifacts" => Artifact::deserialize(entry).map(|_| ()),
+                    ARTIFACT_SETS => ArtifactSet::deserialize(entry).map(|_| ()),
+                    _ => Item::deserialize(entry).map(|_| ()),
+                };
+                if let Err(e) = parsed {
+                    self.report(&pointer, e.to_string());
+                }
+
+                if category == "weapons" && entry.get("weapon_attr").is_none_or(Value::is_null) {
+                    self.report(&pointer, "weapons must have a `weapon_attr`".to_string());
+                }
+                self.check_crit_rate(&pointer, entry);
+
+                if let Some(set) = entry.get("set").and_then(Value::as_str) {
+                    set_refs.push((format!("{}/set", pointer), set));
+                }
+                if let Some(name) = entry.get("name").and_then(Value::as_str) {
+                    let names = if category == ARTIFACT_SETS {
+                        &mut set_names
+                    } else {
+                        &mut names
+                    };
+                    names
+                        .entry(name)
+                        .or_default()
+                        .push(format!("{}/name", pointer));
+                }
+            }
+        }
+
+        for (pointer, set) in set_refs {
+            if !set_names.contains_key(set) {
+                self.report(&pointer, format!("unknown artifact set `{}`", set));
+            }
+        }
+        self.check_duplicates(names);
+        self.check_duplicates(set_names);
+    }
+
+    /// Report every name used more than once, given the pointers to each use.
+    fn check_duplicates(&mut self, names: HashMap<&str, Vec<String>>) {
+        for (name, mut pointers) in names {
+            // the categories are not kept in the file order, the first one is the one on top
+            pointers.sort_by_key(|p| self.positions.get(p).copied());
+            for pointer in &pointers[1..] {
+                let message = format!("duplicate name `{}`, already used by {}", name, pointers[0]);
+                self.report(pointer, message);
+            }
+        }
+    }
+
+    /// Check every `crit_rate` in a value and the values nested in it.
+    fn check_crit_rate(&mut self, pointer: &str, value: &Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    let pointer = format!("{}/{}", pointer, escape(key));
+                    match value.as_u64() {
+                        Some(rate) if key == "crit_rate" && rate > MAX_CRIT_RATE => self.report(
+                            &pointer,
+                            format!("crit_rate must be at most {}, got {}", MAX_CRIT_RATE, rate),
+                        ),
+                        _ => self.check_crit_rate(&pointer, value),
+                    }
+                }
+            }
+            Value::Array(values) => {
+                for (i, value) in values.iter().enumerate() {
+                    self.check_crit_rate(&format!("{}/{}", pointer, i), value);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Escape a key to be used in a JSON pointer.
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Find the line and column of every value in a valid JSON document, by their JSON pointers.
+fn locate(src: &str) -> HashMap<String, (usize, usize)> {
+    let mut scanner = Scanner {
+        src: src.as_bytes(),
+        pos: 0,
+        line: 1,
+        column: 1,
+        found: HashMap::new(),
+    };
+    scanner.value(String::new());
+    scanner.found
+}
+
+struct Scanner<'a> {
+    src: &'a [u8],
+    pos: usize,
+    line: usize,
+    column: usize,
+    found: HashMap<String, (usize, usize)>,
+}
+
+impl Scanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) {
+        let Some(byte) = self.peek() else { return };
+        self.pos += 1;
+        if byte == b'\n' {
+            self.line += 1;
+            self.column = 1;
+        } else if byte & 0xC0 != 0x80 {
+            // only count the first byte of a multi-byte character
+            self.column += 1;
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.bump();
+        }
+    }
+
+    fn value(&mut self, pointer: String) {
+        self.skip_whitespace();
+        self.found.insert(pointer.clone(), (self.line, self.column));
+        match self.peek() {
+            Some(b'{') => {
+                self.bump();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b',') => self.bump(),
+                        Some(b'"') => {
+                            let key = self.string();
+                            self.skip_whitespace();
+                            // the colon
+                            self.bump();
+                            self.value(format!("{}/{}", pointer, escape(&key)));
+                        }
+                        _ => break self.bump(),
+                    }
+                }
+            }
+            Some(b'[') => {
+                self.bump();
+                let mut index = 0;
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b',') => self.bump(),
+                        Some(b']') | None => break self.bump(),
+                        _ => {
+                            self.value(format!("{}/{}", pointer, index));
+                            index += 1;
+                        }
+                    }
+                }
+            }
+            Some(b'"') => {
+                self.string();
+            }
+            _ => {
+                while matches!(self.peek(), Some(b) if !b",]} \t\n\r".contains(&b)) {
+                    self.bump();
+                }
+            }
+        }
+    }
+
+    /// Skip a string and return its content.
+    fn string(&mut self) -> String {
+        let start = self.pos;
+        self.bump();
+        while let Some(byte) = self.peek() {
+            self.bump();
+            match byte {
+                b'\\' => self.bump(),
+                b'"' => break,
+                _ => (),
+            }
+        }
+        serde_json::from_slice(&self.src[start..self.pos]).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod validate_tests {
+    use super::*;
+    use crate::game_data::Position;
+
+    fn problems(src: &str) -> Vec<String> {
+        parse(Path::new("items.json"), src, Format::Json)
+            .unwrap_err()
+            .iter()
+            .map(|p| p.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn locate_values() {
+        let src = "{\n  \"a\": [1, {\"b/c\": \"ü\", \"d\": null}],\n  \"e\": true\n}";
+        let found = locate(src);
+        assert_eq!(found[""], (1, 1));
+        assert_eq!(found["/a"], (2, 8));
+        assert_eq!(found["/a/0"], (2, 9));
+        assert_eq!(found["/a/1/b~1c"], (2, 20));
+        assert_eq!(found["/a/1/d"], (2, 30));
+        assert_eq!(found["/e"], (3, 8));
+    }
+
+    #[test]
+    fn bundled_items_are_valid() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("res/items.json");
+        let items = load(&path).unwrap();
+        assert!(items.weapons.is_some());
+    }
+
+    #[test]
+    fn other_formats() {
+        let src =
+            std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("res/items.json"))
+                .unwrap();
+        let expected =
+            serde_json::to_value(parse(Path::new("items.json"), &src, Format::Json).unwrap())
+                .unwrap();
+        for format in Format::ALL.into_iter().filter(Format::is_enabled) {
+            let converted = crate::format::convert(&src, Format::Json, format).unwrap();
+            let items = parse(Path::new("items"), &converted, format).unwrap();
+            assert_eq!(serde_json::to_value(items).unwrap(), expected, "{}", format);
+        }
+    }
+
+    #[test]
+    fn unknown_location() {
+        let src = "weapons = 3";
+        let err = parse(Path::new("items.toml"), src, Format::Toml).unwrap_err();
+        if Format::Toml.is_enabled() {
+            assert_eq!(err[0].to_string(), "items.toml: /weapons: expected a list");
+        } else {
+            assert_eq!(
+                err[0].to_string(),
+                "items.toml: TOML support is not enabled, rebuild with the `toml` feature"
+            );
+        }
+    }
+
+    #[test]
+    fn syntax_error() {
+        assert_eq!(
+            problems("{\n  \"weapons\": [\n}"),
+            vec!["items.json:3:1: expected value at line 3 column 1"]
+        );
+    }
+
+    #[test]
+    fn every_problem_is_reported() {
+        let src = r#"{
+  "weapons": [
+    { "name": "Sword", "description": "", "rarity": "Normal" },
+    {
+      "name": "Axe", "description": "", "rarity": "Normal",
+      "weapon_attr": { "level": 0, "damage": 1, "ex_damage": 0, "ex_duration": 0, "crit_rate": 120, "crit_dmg": 0 }
+    }
+  ],
+  "armours": [
+    { "name": "Sword", "description": "", "rarity": "Lgendary" }
+  ],
+  "weapon": []
+}"#;
+        assert_eq!(
+            problems(src),
+            vec![
+                "items.json:3:5: /weapons/0: weapons must have a `weapon_attr`",
+                "items.json:6:96: /weapons/1/weapon_attr/crit_rate: crit_rate must be at most 100, got 120",
+                "items.json:10:5: /armours/0: unknown variant `Lgendary`, expected one of `Normal`, `Special`, `Rare`, `Epic`, `Legendary`",
+                "items.json:10:15: /armours/0/name: duplicate name `Sword`, already used by /weapons/0/name",
+                "items.json:12:13: /weapon: unknown category `weapon`, expected one of: armours, artifacts, characters, consumables, materials, tradable, weapons, artifact_sets",
+            ]
+        );
+    }
+
+    #[test]
+    fn wrong_shape() {
+        assert_eq!(
+            problems(r#"{"characters": {"name": "Mona"}}"#),
+            vec!["items.json:1:16: /characters: expected a list"]
+        );
+        assert_eq!(
+            problems("[]"),
+            vec!["items.json:1:1: expected an object of item lists"]
+        );
+    }
+
+    #[test]
+    fn artifact_sets() {
+        let src = r#"{
+  "artifacts": [
+    { "name": "Helm", "set": "Berserker", "position": "Head", "rarity": "Rare", "damage": 5 },
+    { "name": "Boots", "set": "Bersrker", "position": "Shoe", "rarity": "Rare" }
+  ],
+  "artifact_sets": [
+    { "name": "Berserker", "two_piece": { "damage": 20 } },
+    { "name": "Berserker", "four_piece": { "crit_rate": 120 } }
+  ]
+}"#;
+        assert_eq!(
+            problems(src),
+            vec![
+                "items.json:4:31: /artifacts/1/set: unknown artifact set `Bersrker`",
+                "items.json:8:15: /artifact_sets/1/name: duplicate name `Berserker`, already used by /artifact_sets/0/name",
+                "items.json:8:57: /artifact_sets/1/four_piece/crit_rate: crit_rate must be at most 100, got 120",
+            ]
+        );
+    }
+
+    #[test]
+    fn old_versions_are_migrated() {
+        let src = r#"{
+  "consumable": [
+    { "name": "Potion", "description": "", "rarity": "Normal" },
+    { "name": "Elixir", "description": "", "rarity": "Rar" }
+  ]
+}"#;
+        assert_eq!(
+            problems(src),
+            vec!["items.json:4:5: /consumables/1: unknown variant `Rar`, expected one of `Normal`, `Special`, `Rare`, `Epic`, `Legendary`"]
+        );
+        let items = parse(
+            Path::new("items.json"),
+            &src.replace("Rar\"", "Rare\""),
+            Format::Json,
+        )
+        .unwrap();
+        assert_eq!(items.category("consumables").unwrap().len(), 2);
+
+        // artifacts shaped like items are still checked where their stats were
+        let src = r#"{
+  "version": 2,
+  "artifacts": [
+    { "name": "Old Ring", "description": "", "rarity": "Rare",
+      "weapon_attr": { "level": 0, "damage": 10, "crit_rate": 150 } }
+  ]
+}"#;
+        assert_eq!(
+            problems(src),
+            vec!["items.json:5:63: /artifacts/0/crit_rate: crit_rate must be at most 100, got 150"]
+        );
+        let items = parse(
+            Path::new("items.json"),
+            &src.replace("150", "15"),
+            Format::Json,
+        )
+        .unwrap();
+        let ring = &items.artifacts()[0];
+        assert_eq!(
+            (ring.position(), ring.damage(), ring.crit_rate()),
+            (Position::Weapon, 10, 15)
+        );
+
+        assert_eq!(
+            problems(r#"{ "version": 9, "consumables": [] }"#),
+            vec![
+                "items.json:1:14: /version: version 9 is newer than the latest supported version 3"
+            ]
+        );
+    }
+}
@@ -0,0 +1,316 @@
+//! Search methods behind a common interface, so they are all measured the same way.
+//!
+//! Every engine searches for several patterns at once with the leftmost-first semantics:
+//! a match starting further left wins, and among matches starting at the same position,
+//! the pattern given first wins. Matches don't overlap, the next one is searched after the end
+//! of the previous one.
+
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
+use memchr::memmem;
+use regex_automata::{Regex, RegexBuilder};
+
+/// A match of a pattern, `pattern` is its index in the patterns the engine was prepared with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Match {
+    pub pattern: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+pub trait SearchEngine {
+    /// Get ready to search for some patterns, e.g. build an automaton.
+    /// This is not part of what is measured.
+    fn prepare(patterns: &[&str]) -> Result<Self, String>
+    where
+        Self: Sized;
+
+    /// Leftmost match of any pattern.
+    fn find_first(&self, haystack: &str) -> Option<Match> {
+        self.find_iter(haystack).next()
+    }
+
+    /// Every match, from left to right.
+    fn find_iter<'a>(&'a self, haystack: &'a str) -> Box<dyn Iterator<Item = Match> + 'a>;
+
+    fn count_all(&self, haystack: &str) -> usize {
+        self.find_iter(haystack).count()
+    }
+}
+
+/// Check what every engine needs, some pattern and no empty one.
+pub fn check_patterns(patterns: &[&str]) -> Result<(), String> {
+    if patterns.is_empty() {
+        Err("there is no pattern to search for".to_string())
+    } else if patterns.iter().any(|p| p.is_empty()) {
+        Err("empty patterns are not supported".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+/// Leftmost-first matches of several patterns, for the engines that search one pattern at a
+/// time. `find(pattern, from)` is the start of the first match of a pattern at or after `from`.
+pub struct Merged<F> {
+    lens: Vec<usize>,
+    /// Start of the next match of each pattern, `None` once there is no more.
+    next: Vec<Option<usize>>,
+    /// End of the previous match.
+    pos: usize,
+    find: F,
+}
+
+impl<F: FnMut(usize, usize) -> Option<usize>> Merged<F> {
+    pub fn new(lens: Vec<usize>, mut find: F) -> Self {
+        let next = (0..lens.len()).map(|i| find(i, 0)).collect();
+        Merged {
+            lens,
+            next,
+            pos: 0,
+            find,
+        }
+    }
+}
+
+impl<F: FnMut(usize, usize) -> Option<usize>> Iterator for Merged<F> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Match> {
+        // the matches overlapping the previous one are skipped, this is done only now
+        // so nothing more is searched after the first match when only it is needed
+        for i in 0..self.next.len() {
+            if self.next[i].is_some_and(|s| s < self.pos) {
+                self.next[i] = (self.find)(i, self.pos);
+            }
+        }
+        let (pattern, start) = self
+            .next
+            .iter()
+            .enumerate()
+            .filter_map(|(i, start)| start.map(|s| (i, s)))
+            .min_by_key(|&(i, start)| (start, i))?;
+        let end = start + self.lens[pattern];
+        self.pos = end;
+        Some(Match {
+            pattern,
+            start,
+            end,
+        })
+    }
+}
+
+/// `str::find` of the standard library.
+pub struct StdEngine {
+    patterns: Vec<String>,
+}
+
+impl SearchEngine for StdEngine {
+    fn prepare(patterns: &[&str]) -> Result<Self, String> {
+        check_patterns(patterns)?;
+        Ok(StdEngine {
+            patterns: patterns.iter().map(|p| p.to_string()).collect(),
+        })
+    }
+
+    fn find_iter<'a>(&'a self, haystack: &'a str) -> Box<dyn Iterator<Item = Match> + 'a> {
+        let lens = self.patterns.iter().map(String::len).collect();
+        // matches end on char boundaries, so does `from`
+        Box::new(Merged::new(lens, move |i, from| {
+            let found = haystack.get(from..)?.find(self.patterns[i].as_str())?;
+            Some(from + found)
+        }))
+    }
+}
+
+/// `memchr::memmem`, one finder for each pattern.
+pub struct MemmemEngine {
+    finders: Vec<memmem::Finder<'static>>,
+}
+
+impl SearchEngine for MemmemEngine {
+    fn prepare(patterns: &[&str]) -> Result<Self, String> {
+        check_patterns(patterns)?;
+        Ok(MemmemEngine {
+            finders: patterns
+                .iter()
+                .map(|p| memmem::Finder::new(p).into_owned())
+                .collect(),
+        })
+    }
+
+    fn find_iter<'a>(&'a self, haystack: &'a str) -> Box<dyn Iterator<Item = Match> + 'a> {
+        let lens = self.finders.iter().map(|f| f.needle().len()).collect();
+        let haystack = haystack.as_bytes();
+        Box::new(Merged::new(lens, move |i, from| {
+            let found = self.finders[i].find(&haystack[from..])?;
+            Some(from + found)
+        }))
+    }
+}
+
+/// The `aho-corasick` crate, one automaton for every pattern.
+pub struct AhoCorasickEngine {
+    aho: AhoCorasick,
+}
+
+impl SearchEngine for AhoCorasickEngine {
+    fn prepare(patterns: &[&str]) -> Result<Self, String> {
+        check_patterns(patterns)?;
+        let aho = AhoCorasickBuilder::new()
+            .match_kind(MatchKind::LeftmostFirst)
+            .build(patterns);
+        Ok(AhoCorasickEngine { aho })
+    }
+
+    fn find_first(&self, haystack: &str) -> Option<Match> {
+        self.aho.find(haystack).map(|m| Match {
+            pattern: m.pattern(),
+            start: m.start(),
+            end: m.end(),
+        })
+    }
+
+    fn find_iter<'a>(&'a self, haystack: &'a str) -> Box<dyn Iterator<Item = Match> + 'a> {
+        Box::new(self.aho.find_iter(haystack).map(|m| Match {
+            pattern: m.pattern(),
+            start: m.start(),
+            end: m.end(),
+        }))
+    }
+
+    fn count_all(&self, haystack: &str) -> usize {
+        self.aho.find_iter(haystack).count()
+    }
+}
+
+/// A dense DFA of `regex-automata`, built from an alternation of every pattern.
+pub struct DfaEngine {
+    patterns: Vec<String>,
+    regex: Regex,
+}
+
+impl DfaEngine {
+    /// The DFA only tells where the match is, the pattern is the first one matching its text.
+    fn to_match(&self, haystack: &str, (start, end): (usize, usize)) -> Match {
+        let text = &haystack[start..end];
+        let pattern = self
+            .patterns
+            .iter()
+            .position(|p| p == text)
+            .expect("the DFA only matches the patterns");
+        Match {
+            pattern,
+            start,
+            end,
+        }
+    }
+}
+
+impl SearchEngine for DfaEngine {
+    fn prepare(patterns: &[&str]) -> Result<Self, String> {
+        check_patterns(patterns)?;
+        let alternation: Vec<String> = patterns.iter().map(|p| escape(p)).collect();
+        let regex = RegexBuilder::new()
+            .build(&alternation.join("|"))
+            .map_err(|e| format!("cannot build the DFA: {}", e))?;
+        Ok(DfaEngine {
+            patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            regex,
+        })
+    }
+
+    fn find_first(&self, haystack: &str) -> Option<Match> {
+        let found = self.regex.find(haystack.as_bytes())?;
+        Some(self.to_match(haystack, found))
+    }
+
+    fn find_iter<'a>(&'a self, haystack: &'a str) -> Box<dyn Iterator<Item = Match> + 'a> {
+        Box::new(
+            self.regex
+                .find_iter(haystack.as_bytes())
+                .map(move |found| self.to_match(haystack, found)),
+        )
+    }
+
+    fn count_all(&self, haystack: &str) -> usize {
+        self.regex.find_iter(haystack.as_bytes()).count()
+    }
+}
+
+/// Escape the characters that mean something in a regular expression.
+fn escape(pattern: &str) -> String {
+    let mut escaped = String::with_capacity(pattern.len());
+    for c in pattern.chars() {
+        if r"\.+*?()|[]{}^$#&-~".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod engine_tests {
+    use super::*;
+    use crate::horspool::HorspoolEngine;
+    use crate::rabin_karp::RabinKarpEngine;
+
+    const HAYSTACK: &str = "xxabcdxxbcxxabcdéxa.cxxbc";
+
+    fn check<E: SearchEngine>() {
+        let name = std::any::type_name::<E>();
+        let engine = E::prepare(&["bcd", "abc", "bc", "a.c", "é"]).unwrap();
+        let matches: Vec<(usize, usize)> = engine
+            .find_iter(HAYSTACK)
+            .map(|m| (m.pattern, m.start))
+            .collect();
+        // "abc" starts before "bcd", and "bc" is skipped when it is in a match
+        assert_eq!(
+            matches,
+            vec![(1, 2), (2, 8), (1, 12), (4, 16), (3, 19), (2, 24)],
+            "{}",
+            name
+        );
+        assert_eq!(engine.count_all(HAYSTACK), 6, "{}", name);
+        assert_eq!(
+            engine.find_first(HAYSTACK),
+            Some(Match {
+                pattern: 1,
+                start: 2,
+                end: 5
+            }),
+            "{}",
+            name
+        );
+
+        // at the same position, the pattern given first wins
+        let engine = E::prepare(&["ab", "abcd", "zz"]).unwrap();
+        assert_eq!(engine.find_first(HAYSTACK).unwrap().pattern, 0, "{}", name);
+        let engine = E::prepare(&["abcd", "ab"]).unwrap();
+        assert_eq!(engine.find_first(HAYSTACK).unwrap().pattern, 0, "{}", name);
+
+        let engine = E::prepare(&["zz"]).unwrap();
+        assert!(engine.find_first(HAYSTACK).is_none(), "{}", name);
+        assert_eq!(engine.count_all(HAYSTACK), 0, "{}", name);
+        assert_eq!(engine.count_all(""), 0, "{}", name);
+
+        assert!(E::prepare(&[]).is_err(), "{}", name);
+        assert!(E::prepare(&["a", ""]).is_err(), "{}", name);
+    }
+
+    #[test]
+    fn engines_agree() {
+        check::<StdEngine>();
+        check::<MemmemEngine>();
+        check::<AhoCorasickEngine>();
+        check::<DfaEngine>();
+        check::<HorspoolEngine>();
+        check::<RabinKarpEngine>();
+    }
+
+    #[test]
+    fn escape_special_characters() {
+        assert_eq!(escape("a.c"), r"a\.c");
+        assert_eq!(escape("(x|y)*"), r"\(x\|y\)\*");
+        assert_eq!(escape("héllo"), "héllo");
+    }
+}
@@ -0,0 +1,99 @@
+//! A hand-written Boyer-Moore-Horspool search.
+
+use crate::engine::{check_patterns, Match, Merged, SearchEngine};
+
+/// Finds a single pattern, comparing it from its last byte and skipping ahead by how far the
+/// byte under its end is from the end of the pattern.
+pub struct Horspool {
+    pattern: Vec<u8>,
+    /// How far to move the pattern when a byte is under its last byte, for every byte.
+    shift: [usize; 256],
+}
+
+impl Horspool {
+    pub fn new(pattern: &[u8]) -> Self {
+        let mut shift = [pattern.len(); 256];
+        // the last byte is not counted, it would shift by 0
+        for (i, &b) in pattern
+            .iter()
+            .enumerate()
+            .take(pattern.len().saturating_sub(1))
+        {
+            shift[b as usize] = pattern.len() - 1 - i;
+        }
+        Horspool {
+            pattern: pattern.to_vec(),
+            shift,
+        }
+    }
+
+    /// Start of the first match in the haystack.
+    pub fn find(&self, haystack: &[u8]) -> Option<usize> {
+        let len = self.pattern.len();
+        if len == 0 {
+            return Some(0);
+        }
+        let last = len - 1;
+        let mut pos = 0;
+        while pos + len <= haystack.len() {
+            let b = haystack[pos + last];
+            if b == self.pattern[last] && haystack[pos..pos + last] == self.pattern[..last] {
+                return Some(pos);
+            }
+            pos += self.shift[b as usize];
+        }
+        None
+    }
+}
+
+/// One [`Horspool`] for each pattern.
+pub struct HorspoolEngine {
+    finders: Vec<Horspool>,
+}
+
+impl SearchEngine for HorspoolEngine {
+    fn prepare(patterns: &[&str]) -> Result<Self, String> {
+        check_patterns(patterns)?;
+        Ok(HorspoolEngine {
+            finders: patterns
+                .iter()
+                .map(|p| Horspool::new(p.as_bytes()))
+                .collect(),
+        })
+    }
+
+    fn find_iter<'a>(&'a self, haystack: &'a str) -> Box<dyn Iterator<Item = Match> + 'a> {
+        let lens = self.finders.iter().map(|f| f.pattern.len()).collect();
+        let haystack = haystack.as_bytes();
+        Box::new(Merged::new(lens, move |i, from| {
+            let found = self.finders[i].find(&haystack[from..])?;
+            Some(from + found)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod horspool_tests {
+    use super::*;
+
+    #[test]
+    fn shift_table() {
+        let finder = Horspool::new(b"abcab");
+        assert_eq!(finder.shift[b'a' as usize], 1);
+        assert_eq!(finder.shift[b'b' as usize], 3);
+        assert_eq!(finder.shift[b'c' as usize], 2);
+        assert_eq!(finder.shift[b'z' as usize], 5);
+    }
+
+    #[test]
+    fn find_a_pattern() {
+        let haystack = b"here is a simple example";
+        assert_eq!(Horspool::new(b"example").find(haystack), Some(17));
+        assert_eq!(Horspool::new(b"simple").find(haystack), Some(10));
+        assert_eq!(Horspool::new(b"h").find(haystack), Some(0));
+        assert_eq!(Horspool::new(b"examples").find(haystack), None);
+        assert_eq!(Horspool::new(b"aaa").find(b"aaaa"), Some(0));
+        assert_eq!(Horspool::new(b"abab").find(b"abaabab"), Some(3));
+        assert_eq!(Horspool::new(b"abc").find(b"ab"), None);
+    }
+}
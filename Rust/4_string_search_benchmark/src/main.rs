@@ -1,237 +1,236 @@
-use aho_corasick::AhoCorasick;
-use memchr::memmem;
-use std::time::{Duration, Instant};
-
-fn parse_duration(duration: Duration) -> String {
-    let dur_micro = duration.as_micros();
-    if dur_micro > 1_000_000 {
-        format!("\x1b[31;1m{} seconds\x1b[0m", duration.as_secs_f32()) // red
-    } else if dur_micro > 1_000 {
-        format!("\x1b[33;1m{} milliseconds\x1b[0m", duration.as_millis()) // yellow
-    } else {
-        format!("\x1b[36;1m{} microseconds\x1b[0m", dur_micro) // cyan
-    }
+mod engine;
+mod horspool;
+mod patterns;
+mod rabin_karp;
+mod stats;
+
+use clap::{value_parser, Arg, Command};
+use engine::{AhoCorasickEngine, DfaEngine, Match, MemmemEngine, SearchEngine, StdEngine};
+use horspool::HorspoolEngine;
+use memmap2::Mmap;
+use rabin_karp::RabinKarpEngine;
+use stats::{Pretty, Stats};
+use std::error::Error;
+use std::fs::File;
+use std::hint::black_box;
+use std::path::PathBuf;
+use std::process;
+use std::time::Instant;
+
+const DEFAULT_CORPUS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/res/longtext");
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    /// Find the leftmost match of any pattern.
+    First,
+    /// Count the matches of every pattern.
+    All,
 }
 
-fn native_search(words: &[&str], context: &[u8], find_all: bool) {
-    let str_context = std::str::from_utf8(context).unwrap();
-    let timer = Instant::now();
-
-    if words.len() == 1 {
-        if find_all {
-            println!(
-                "[native] Found {} occurance of word \"{}\" in {}",
-                str_context.match_indices(words[0]).count(),
-                words[0],
-                parse_duration(timer.elapsed())
-            );
-        } else {
-            let result = str_context.find(words[0]);
-            match result {
-                Some(n) => {
-                    println!(
-                        "[native] Found word \"{}\" at position {} in {}",
-                        words[0],
-                        n,
-                        parse_duration(timer.elapsed())
-                    );
-                }
-                None => {
-                    println!(
-                        "[native] Did not find word \"{}\", time elapsed: {}",
-                        words[0],
-                        parse_duration(timer.elapsed())
-                    );
-                }
-            }
-        }
-    } else {
-        let mut counter: usize = 0;
-        for &word in words {
-            if find_all {
-                counter += str_context.match_indices(word).count();
-            } else {
-                match str_context.find(word) {
-                    Some(_) => {
-                        counter += 1;
-                        break;
-                    }
-                    None => {}
-                }
-            }
-        }
+/// What a search found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Outcome {
+    First(Option<Match>),
+    Count(usize),
+}
 
-        println!(
-            "[native] Found {} occurance of words \"{:?}\" in {}",
-            counter,
-            words,
-            parse_duration(timer.elapsed())
-        );
+impl Outcome {
+    fn describe(&self, patterns: &[&str]) -> String {
+        match self {
+            Outcome::First(Some(m)) => format!("\"{}\" at {}", patterns[m.pattern], m.start),
+            Outcome::First(None) => "not found".to_string(),
+            Outcome::Count(n) => format!("{} occurrences", n),
+        }
     }
 }
 
-fn memmem_search(words: &[&str], context: &[u8], find_all: bool) {
-    // start timer
-    let timer = Instant::now();
-
-    if words.len() == 1 {
-        if find_all {
-            let mem_it = memmem::find_iter(context, words[0]);
-            println!(
-                "[memmem] Found {} occurance of word \"{}\" in {}",
-                mem_it.count(),
-                words[0],
-                parse_duration(timer.elapsed())
-            );
-        } else {
-            let finder = memmem::Finder::new(words[0]);
-            match finder.find(context) {
-                Some(n) => {
-                    println!(
-                        "[memmem] Found word \"{}\" at position {} in {}",
-                        words[0],
-                        n,
-                        parse_duration(timer.elapsed())
-                    );
-                }
-                None => {
-                    println!(
-                        "[memmem] Did not find word \"{}\", time elapsed: {}",
-                        words[0],
-                        parse_duration(timer.elapsed())
-                    );
-                }
-            }
+/// How many times each search runs.
+struct Harness {
+    /// Runs before the measured ones, to warm up the caches, not counted.
+    warmup: u32,
+    iterations: u32,
+}
+
+impl Harness {
+    /// Run a search of an engine the warmup times then the measured times,
+    /// returning what it found in the last run and how long the measured runs took.
+    fn run<E: SearchEngine + ?Sized>(
+        &self,
+        engine: &E,
+        haystack: &str,
+        mode: Mode,
+    ) -> (Outcome, Stats) {
+        let search = || match mode {
+            Mode::First => Outcome::First(engine.find_first(haystack)),
+            Mode::All => Outcome::Count(engine.count_all(haystack)),
+        };
+        for _ in 0..self.warmup {
+            black_box(search());
         }
-    } else {
-        let mut counter: usize = 0;
-
-        for &word in words {
-            if find_all {
-                counter += memmem::find_iter(context, word).count();
-            } else {
-                let finder = memmem::Finder::new(word);
-                match finder.find(context) {
-                    Some(_) => {
-                        counter += 1;
-                        break;
-                    }
-                    None => {}
-                }
-            }
+        let mut durations = Vec::with_capacity(self.iterations as usize);
+        let mut outcome = Outcome::Count(0);
+        for _ in 0..self.iterations {
+            let timer = Instant::now();
+            outcome = black_box(search());
+            durations.push(timer.elapsed());
         }
-
-        println!(
-            "[memmem] Found {} occurance of words \"{:?}\" in {}",
-            counter,
-            words,
-            parse_duration(timer.elapsed())
-        );
+        let stats = Stats::new(&durations).expect("at least one iteration");
+        (outcome, stats)
     }
 }
 
-fn aho_corasick_seach(words: &[&str], context: &[u8], find_all: bool) {
-    let aho = AhoCorasick::new(words);
-
-    // start timer
-    let timer = Instant::now();
-
-    if find_all {
-        let result_count = aho.find_iter(context).count();
-        println!(
-            "[aho-corasick] Found {} occurance of words \"{:?}\" in {}",
-            result_count,
-            words,
-            parse_duration(timer.elapsed())
-        );
-    } else {
-        let found = aho.find(context);
-        match found {
-            Some(m) => {
-                println!(
-                    "[aho-corasick] Found leftmost match \"{}\" at position {} in {}",
-                    std::str::from_utf8(&context[m.start()..m.end()]).unwrap(),
-                    m.start(),
-                    parse_duration(timer.elapsed())
-                );
-            }
-            None => {
-                println!(
-                    "[aho-corasick] Did not find any word in list \"{:?}\", time elapsed: {}",
-                    words,
-                    parse_duration(timer.elapsed())
-                );
-            }
-        }
-    }
+/// An engine ready to search, or why it could not get ready.
+type Prepared = Result<Box<dyn SearchEngine>, String>;
+type Prepare = fn(&[&str]) -> Prepared;
+
+fn prepare<E: SearchEngine + 'static>(patterns: &[&str]) -> Prepared {
+    Ok(Box::new(E::prepare(patterns)?))
+}
+
+/// Every engine to compare, by name.
+const ENGINES: [(&str, Prepare); 6] = [
+    ("std", prepare::<StdEngine>),
+    ("memmem", prepare::<MemmemEngine>),
+    ("aho-corasick", prepare::<AhoCorasickEngine>),
+    ("dfa", prepare::<DfaEngine>),
+    ("horspool", prepare::<HorspoolEngine>),
+    ("rabin-karp", prepare::<RabinKarpEngine>),
+];
+
+fn cli() -> Command {
+    Command::new(env!("CARGO_PKG_NAME"))
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Compare substring search engines on a long text")
+        .arg(
+            Arg::new("corpus")
+                .long("corpus")
+                .short('c')
+                .value_name("PATH")
+                .help("Text to search in, memory-mapped")
+                .value_parser(value_parser!(PathBuf))
+                .default_value(DEFAULT_CORPUS),
+        )
+        .arg(
+            Arg::new("patterns")
+                .long("patterns")
+                .short('p')
+                .value_name("PATH")
+                .help(
+                    "File with the lists of patterns to search for, one list on each line \
+                     with its patterns separated by whitespace",
+                )
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("iterations")
+                .long("iterations")
+                .short('n')
+                .value_name("N")
+                .help("Measured runs of each search")
+                .value_parser(value_parser!(u32).range(1..))
+                .default_value("10"),
+        )
+        .arg(
+            Arg::new("warmup")
+                .long("warmup")
+                .short('w')
+                .value_name("N")
+                .help("Runs of each search before the measured ones")
+                .value_parser(value_parser!(u32))
+                .default_value("2"),
+        )
 }
 
 fn main() {
-    // load text content which contains 1,000,000 words
-    let full_text = include_bytes!("../res/longtext");
-
-    // the first word being searched
-    let word_1 = "B14fNx8YUy";
-    // a non-existing word to be test
-    let non_exist_word = "uwuwwwwxoxouuu";
-
-    let three_words = &["tHGp3FEBx7", "skJCoFTN93", "fNx8YU"];
-
-    let ten_words = &[
-        "Hq6JYTKV",
-        "7SC4yc4Vt",
-        "58PjWm",
-        "3tRoIEot",
-        "97OUJh",
-        "i6xiuKP",
-        "fbkB0PB",
-        "RUfmE4la",
-        "H46v",
-        "A_NON_EXISTING_WORD",
-    ];
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
 
-    println!(
-        "\n==================== Finding non-existing string (worst-case) ===================="
-    );
-    native_search(&[non_exist_word], full_text, true);
-    memmem_search(&[non_exist_word], full_text, true);
-    aho_corasick_seach(&[non_exist_word], full_text, true);
+fn run() -> Result<(), Box<dyn Error>> {
+    let matches = cli().get_matches();
+    let corpus = matches.get_one::<PathBuf>("corpus").unwrap();
+    let harness = Harness {
+        warmup: *matches.get_one::<u32>("warmup").unwrap(),
+        iterations: *matches.get_one::<u32>("iterations").unwrap(),
+    };
+    let lists = match matches.get_one::<PathBuf>("patterns") {
+        Some(path) => {
+            patterns::load(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?
+        }
+        None => patterns::default_lists(),
+    };
+    if lists.is_empty() {
+        return Err("there is no pattern to search for".into());
+    }
 
-    println!(
-        "\n=================== Finding first occurrance of a single string ==================="
-    );
-    native_search(&[word_1], full_text, false);
-    memmem_search(&[word_1], full_text, false);
-    aho_corasick_seach(&[word_1], full_text, false);
+    let file =
+        File::open(corpus).map_err(|e| format!("cannot open {}: {}", corpus.display(), e))?;
+    if file.metadata()?.len() == 0 {
+        return Err(format!(
+            "{} is empty, there is nothing to search in",
+            corpus.display()
+        )
+        .into());
+    }
+    // SAFETY: the corpus is only read, it should not be modified while the benchmark runs
+    let mmap = unsafe { Mmap::map(&file) }
+        .map_err(|e| format!("cannot map {}: {}", corpus.display(), e))?;
+    let context = std::str::from_utf8(&mmap)
+        .map_err(|e| format!("{} is not UTF-8: {}", corpus.display(), e))?;
 
     println!(
-        "\n==================== Finding all occurrance of a single string ===================="
+        "Searching in {} ({} bytes), {} warmup and {} measured runs of each search",
+        corpus.display(),
+        context.len(),
+        harness.warmup,
+        harness.iterations
     );
-    native_search(&[word_1], full_text, true);
-    memmem_search(&[word_1], full_text, true);
-    aho_corasick_seach(&[word_1], full_text, true);
+    for list in &lists {
+        let words: Vec<&str> = list.iter().map(String::as_str).collect();
+        // preparing is not measured, it is done once for both modes
+        let engines: Vec<(&str, Prepared)> = ENGINES
+            .iter()
+            .map(|(name, prepare)| (*name, prepare(&words)))
+            .collect();
+        for mode in [Mode::First, Mode::All] {
+            let title = match mode {
+                Mode::First => "Finding the leftmost occurrence of",
+                Mode::All => "Finding all occurrences of",
+            };
+            println!("\n=============== {} {:?} ===============", title, words);
+            print_header();
+            for (name, engine) in &engines {
+                match engine {
+                    Ok(engine) => {
+                        let (outcome, stats) = harness.run(engine.as_ref(), context, mode);
+                        print_result(name, &outcome.describe(&words), &stats);
+                    }
+                    Err(e) => println!("{:<14}{}", name, e),
+                }
+            }
+        }
+    }
+    Ok(())
+}
 
+fn print_header() {
     println!(
-        "\n==================== Finding left most occurrance of three strings ===================="
+        "{:<14}{:<24}{:>12}{:>12}{:>12}{:>12}{:>12}",
+        "engine", "result", "min", "median", "p95", "mean", "stddev"
     );
-    native_search(three_words, full_text, false);
-    memmem_search(three_words, full_text, false);
-    aho_corasick_seach(three_words, full_text, false);
-
-    println!("\n==================== Finding all occurrance of three strings ====================");
-    native_search(three_words, full_text, true);
-    memmem_search(three_words, full_text, true);
-    aho_corasick_seach(three_words, full_text, true);
+}
 
+fn print_result(engine: &str, result: &str, stats: &Stats) {
     println!(
-        "\n==================== Finding left most occurrance of ten strings ===================="
+        "{:<14}{:<24}{:>12}{:>12}{:>12}{:>12}{:>12}",
+        engine,
+        result,
+        Pretty(stats.min),
+        Pretty(stats.median),
+        Pretty(stats.p95),
+        Pretty(stats.mean),
+        Pretty(stats.stddev)
     );
-    native_search(ten_words, full_text, false);
-    memmem_search(ten_words, full_text, false);
-    aho_corasick_seach(ten_words, full_text, false);
-
-    println!("\n==================== Finding all occurrance of ten strings ====================");
-    native_search(ten_words, full_text, true);
-    memmem_search(ten_words, full_text, true);
-    aho_corasick_seach(ten_words, full_text, true);
 }
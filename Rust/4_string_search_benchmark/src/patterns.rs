@@ -0,0 +1,62 @@
+use std::io;
+use std::path::Path;
+
+/// Lists searched when no pattern file is given, made for `res/longtext`.
+pub const DEFAULT_LISTS: [&[&str]; 4] = [
+    // a non-existing word, the worst case
+    &["uwuwwwwxoxouuu"],
+    &["B14fNx8YUy"],
+    &["tHGp3FEBx7", "skJCoFTN93", "fNx8YU"],
+    &[
+        "Hq6JYTKV",
+        "7SC4yc4Vt",
+        "58PjWm",
+        "3tRoIEot",
+        "97OUJh",
+        "i6xiuKP",
+        "fbkB0PB",
+        "RUfmE4la",
+        "H46v",
+        "A_NON_EXISTING_WORD",
+    ],
+];
+
+pub fn default_lists() -> Vec<Vec<String>> {
+    DEFAULT_LISTS
+        .iter()
+        .map(|list| list.iter().map(|p| p.to_string()).collect())
+        .collect()
+}
+
+/// Read lists of patterns from a file, see [`parse`].
+pub fn load(path: &Path) -> io::Result<Vec<Vec<String>>> {
+    std::fs::read_to_string(path).map(|src| parse(&src))
+}
+
+/// Parse lists of patterns, one list on each line with its patterns separated by whitespace.
+/// Empty lines and lines starting with `#` are skipped.
+pub fn parse(src: &str) -> Vec<Vec<String>> {
+    src.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.split_whitespace().map(String::from).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod patterns_tests {
+    use super::*;
+
+    #[test]
+    fn parse_lists() {
+        let src = "# worst case\nuwuwwwwxoxouuu\n\n  tHGp3FEBx7 skJCoFTN93\tfNx8YU  \n";
+        assert_eq!(
+            parse(src),
+            vec![
+                vec!["uwuwwwwxoxouuu"],
+                vec!["tHGp3FEBx7", "skJCoFTN93", "fNx8YU"]
+            ]
+        );
+        assert!(parse("").is_empty());
+    }
+}
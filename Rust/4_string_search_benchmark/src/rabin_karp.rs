@@ -0,0 +1,184 @@
+//! A hand-written Rabin-Karp search for several patterns at once.
+
+use crate::engine::{check_patterns, Match, SearchEngine};
+
+/// Base of the polynomial hash, computed modulo 2^64.
+const BASE: u64 = 257;
+
+/// Hash of some bytes, the same as rolling a window over them.
+fn hash(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(0, |h, &b| h.wrapping_mul(BASE).wrapping_add(u64::from(b)))
+}
+
+/// Bit of a hash in the filter of a bucket. The low bits of the hash mostly depend on the sum
+/// of the bytes, so the hash is mixed first, then its top 8 bits pick one of 256 bits.
+fn filter_bit(hash: u64) -> usize {
+    (hash.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 56) as usize
+}
+
+/// Patterns of the same length.
+struct Bucket {
+    len: usize,
+    /// `BASE^(len - 1)`, the weight of the byte leaving the window.
+    weight: u64,
+    /// The [`filter_bit`] of the hash of every pattern, so most windows are ruled out without
+    /// searching `hashes`.
+    filter: [u64; 4],
+    /// Distinct hashes of the patterns, sorted.
+    hashes: Vec<u64>,
+    /// Indexes of the patterns with each hash of `hashes`, in ascending order.
+    patterns: Vec<Vec<usize>>,
+}
+
+impl Bucket {
+    fn new(len: usize) -> Self {
+        Bucket {
+            len,
+            weight: (1..len).fold(1, |w: u64, _| w.wrapping_mul(BASE)),
+            filter: [0; 4],
+            hashes: vec![],
+            patterns: vec![],
+        }
+    }
+
+    fn insert(&mut self, hash: u64, pattern: usize) {
+        match self.hashes.binary_search(&hash) {
+            Ok(pos) => self.patterns[pos].push(pattern),
+            Err(pos) => {
+                self.hashes.insert(pos, hash);
+                self.patterns.insert(pos, vec![pattern]);
+                let bit = filter_bit(hash);
+                self.filter[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+    }
+
+    /// Indexes of the patterns with a hash.
+    fn get(&self, hash: u64) -> &[usize] {
+        let bit = filter_bit(hash);
+        if self.filter[bit / 64] & (1 << (bit % 64)) == 0 {
+            return &[];
+        }
+        match self.hashes.binary_search(&hash) {
+            Ok(pos) => &self.patterns[pos],
+            Err(_) => &[],
+        }
+    }
+}
+
+/// Rolls a hash over the haystack for each length of pattern, and only compares the bytes of
+/// the patterns with the same hash as the window.
+pub struct RabinKarpEngine {
+    patterns: Vec<Vec<u8>>,
+    buckets: Vec<Bucket>,
+}
+
+impl RabinKarpEngine {
+    /// Leftmost-first match starting at or after `from`.
+    fn find_at(&self, haystack: &[u8], from: usize) -> Option<Match> {
+        // the hash of a bucket is only meaningful while its window fits in the haystack
+        let mut hashes: Vec<u64> = self
+            .buckets
+            .iter()
+            .map(|b| haystack.get(from..from + b.len).map_or(0, hash))
+            .collect();
+
+        for pos in from..haystack.len() {
+            let mut found: Option<usize> = None;
+            for (bucket, h) in self.buckets.iter().zip(&mut hashes) {
+                if pos + bucket.len > haystack.len() {
+                    continue;
+                }
+                // the patterns are in ascending order, only the first match could win
+                if let Some(&i) = bucket.get(*h).iter().find(|&&i| {
+                    found.is_none_or(|f| i < f) && haystack[pos..].starts_with(&self.patterns[i])
+                }) {
+                    found = Some(i);
+                }
+                // rolled even if something was found, it is not used after that
+                if let Some(&next) = haystack.get(pos + bucket.len) {
+                    *h = h
+                        .wrapping_sub(u64::from(haystack[pos]).wrapping_mul(bucket.weight))
+                        .wrapping_mul(BASE)
+                        .wrapping_add(u64::from(next));
+                }
+            }
+            if let Some(pattern) = found {
+                return Some(Match {
+                    pattern,
+                    start: pos,
+                    end: pos + self.patterns[pattern].len(),
+                });
+            }
+        }
+        None
+    }
+}
+
+impl SearchEngine for RabinKarpEngine {
+    fn prepare(patterns: &[&str]) -> Result<Self, String> {
+        check_patterns(patterns)?;
+        let mut buckets: Vec<Bucket> = vec![];
+        for (i, pattern) in patterns.iter().enumerate() {
+            let len = pattern.len();
+            let bucket = match buckets.iter().position(|b| b.len == len) {
+                Some(pos) => &mut buckets[pos],
+                None => {
+                    buckets.push(Bucket::new(len));
+                    buckets.last_mut().unwrap()
+                }
+            };
+            bucket.insert(hash(pattern.as_bytes()), i);
+        }
+        Ok(RabinKarpEngine {
+            patterns: patterns.iter().map(|p| p.as_bytes().to_vec()).collect(),
+            buckets,
+        })
+    }
+
+    fn find_first(&self, haystack: &str) -> Option<Match> {
+        self.find_at(haystack.as_bytes(), 0)
+    }
+
+    fn find_iter<'a>(&'a self, haystack: &'a str) -> Box<dyn Iterator<Item = Match> + 'a> {
+        let haystack = haystack.as_bytes();
+        let mut from = 0;
+        Box::new(std::iter::from_fn(move || {
+            let found = self.find_at(haystack, from)?;
+            from = found.end;
+            Some(found)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod rabin_karp_tests {
+    use super::*;
+
+    #[test]
+    fn rolling_hash() {
+        let engine = RabinKarpEngine::prepare(&["cde"]).unwrap();
+        let bucket = &engine.buckets[0];
+        // rolling "bcd" into "cde" gives the hash of "cde"
+        let rolled = hash(b"bcd")
+            .wrapping_sub(u64::from(b'b').wrapping_mul(bucket.weight))
+            .wrapping_mul(BASE)
+            .wrapping_add(u64::from(b'e'));
+        assert_eq!(rolled, hash(b"cde"));
+    }
+
+    #[test]
+    fn buckets_by_length() {
+        let engine = RabinKarpEngine::prepare(&["ab", "xyz", "cd", "ab"]).unwrap();
+        let lens: Vec<usize> = engine.buckets.iter().map(|b| b.len).collect();
+        assert_eq!(lens, vec![2, 3]);
+        assert_eq!(engine.buckets[0].get(hash(b"ab")), [0, 3]);
+        assert_eq!(engine.buckets[0].hashes.len(), 2);
+        assert!(engine.buckets[0].get(hash(b"zz")).is_empty());
+        // the same pattern given twice is found as the first one
+        assert_eq!(engine.find_first("xxabxyz").unwrap().pattern, 0);
+        assert_eq!(engine.count_all("abcdxyzab"), 4);
+    }
+}
@@ -0,0 +1,113 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Summary of the durations of several runs of the same search.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    /// 95th percentile, by the nearest rank.
+    pub p95: Duration,
+    pub mean: Duration,
+    /// Standard deviation of the whole population of runs.
+    pub stddev: Duration,
+}
+
+impl Stats {
+    /// Summarize some durations, `None` if there is none.
+    pub fn new(durations: &[Duration]) -> Option<Stats> {
+        if durations.is_empty() {
+            return None;
+        }
+        let mut sorted = durations.to_vec();
+        sorted.sort();
+        let n = sorted.len();
+
+        let median = if n.is_multiple_of(2) {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+        } else {
+            sorted[n / 2]
+        };
+        let p95_rank = (n * 95).div_ceil(100);
+        let mean = sorted.iter().sum::<Duration>() / n as u32;
+        let variance = sorted
+            .iter()
+            .map(|d| (d.as_secs_f64() - mean.as_secs_f64()).powi(2))
+            .sum::<f64>()
+            / n as f64;
+
+        Some(Stats {
+            runs: n,
+            min: sorted[0],
+            median,
+            p95: sorted[p95_rank - 1],
+            mean,
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        })
+    }
+}
+
+/// Format a duration in the largest unit it has at least one of, e.g. `321.40 ms`.
+pub struct Pretty(pub Duration);
+
+impl fmt::Display for Pretty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let nanos = self.0.as_nanos();
+        let (value, unit) = if nanos >= 1_000_000_000 {
+            (self.0.as_secs_f64(), "s")
+        } else if nanos >= 1_000_000 {
+            (nanos as f64 / 1e6, "ms")
+        } else if nanos >= 1_000 {
+            (nanos as f64 / 1e3, "µs")
+        } else {
+            (nanos as f64, "ns")
+        };
+        let s = format!("{:.2} {}", value, unit);
+        f.pad(&s)
+    }
+}
+
+#[cfg(test)]
+mod stats_tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn summarize() {
+        let durations: Vec<Duration> = [5, 1, 4, 2, 3].into_iter().map(ms).collect();
+        let stats = Stats::new(&durations).unwrap();
+        assert_eq!(stats.runs, 5);
+        assert_eq!((stats.min, stats.median, stats.p95), (ms(1), ms(3), ms(5)));
+        assert_eq!(stats.mean, ms(3));
+        // sqrt(2)
+        assert_eq!(stats.stddev.as_micros(), 1414);
+
+        let even = Stats::new(&[ms(1), ms(2), ms(4), ms(10)]).unwrap();
+        assert_eq!(even.median, ms(3));
+        assert!(Stats::new(&[]).is_none());
+    }
+
+    #[test]
+    fn p95_by_nearest_rank() {
+        let durations: Vec<Duration> = (1..=100).map(ms).collect();
+        assert_eq!(Stats::new(&durations).unwrap().p95, ms(95));
+        let durations: Vec<Duration> = (1..=10).map(ms).collect();
+        assert_eq!(Stats::new(&durations).unwrap().p95, ms(10));
+        assert_eq!(Stats::new(&[ms(7)]).unwrap().p95, ms(7));
+    }
+
+    #[test]
+    fn pretty_durations() {
+        assert_eq!(Pretty(Duration::from_nanos(999)).to_string(), "999.00 ns");
+        assert_eq!(Pretty(Duration::from_micros(1500)).to_string(), "1.50 ms");
+        assert_eq!(Pretty(Duration::from_millis(2500)).to_string(), "2.50 s");
+        assert_eq!(
+            format!("{:>10}", Pretty(Duration::from_micros(31))),
+            "  31.00 µs"
+        );
+    }
+}